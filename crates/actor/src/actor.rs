@@ -7,7 +7,7 @@ use embassy_sync::{
     channel::{Channel, DynamicSender, Sender},
 };
 
-use embassy_time::{Duration, Timer};
+use embassy_time::{Duration, Instant, Timer};
 
 pub type Inbox<M, MUTEX, const N: usize> = Sender<'static, MUTEX, M, N>;
 pub type DynamicInbox<M> = DynamicSender<'static, M>;
@@ -20,6 +20,16 @@ pub trait ActorRuntime {
     async fn on_idle(&mut self);
 
     async fn on_message_received(&mut self, message: Self::Message);
+
+    /// Waits for something the actor handles itself, e.g. an interrupt or a deadline of its own,
+    /// before [`ActorRuntime::on_wake_up`] is called. Never returns by default.
+    ///
+    /// The future is dropped when a message or the idle timeout comes first.
+    async fn wait_for_wake_up(&mut self) {
+        core::future::pending().await
+    }
+
+    async fn on_wake_up(&mut self) {}
 }
 
 pub struct Actor<A, M, const QUEUE_SIZE: usize, const IDLE_TIMEOUT_MS: u64>
@@ -48,13 +58,25 @@ where
         let actor = self.actor.init(actor);
         actor.on_init().await;
 
+        let idle_timeout = Duration::from_millis(IDLE_TIMEOUT_MS);
+        // Wake-ups do not delay the idle timeout, the actor may wake up more often than that
+        let mut idle_at = Instant::now() + idle_timeout;
+
         loop {
             let receive_message = self.channel.receive();
-            let timeout = Timer::after(Duration::from_millis(IDLE_TIMEOUT_MS));
+            let timeout = Timer::at(idle_at);
+            let wake_up = actor.wait_for_wake_up();
 
-            match select(receive_message, timeout).await {
-                Either::First(message) => actor.on_message_received(message).await,
-                Either::Second(_) => actor.on_idle().await,
+            match select3(receive_message, timeout, wake_up).await {
+                Either3::First(message) => {
+                    actor.on_message_received(message).await;
+                    idle_at = Instant::now() + idle_timeout;
+                }
+                Either3::Second(_) => {
+                    actor.on_idle().await;
+                    idle_at = Instant::now() + idle_timeout;
+                }
+                Either3::Third(_) => actor.on_wake_up().await,
             }
         }
     }
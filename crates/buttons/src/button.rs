@@ -0,0 +1,321 @@
+use crate::{ButtonConfig, Event, Id, Kind, Length, Ms, RepeatedPressMode};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum State {
+    Released,
+    Debouncing,
+    Pressed,
+}
+
+/// Most events a single update of a button can emit: a gesture timing out, then a raw press, the
+/// press and a repeat when the press needs no debouncing.
+///
+/// Holds are counted from the press event, so never come along with it.
+const MAX_EVENTS_PER_UPDATE: usize = 4;
+
+/// Events emitted by a single update of a button, in order.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Emitted {
+    events: [Event; MAX_EVENTS_PER_UPDATE],
+    len: usize,
+}
+
+impl Emitted {
+    pub(crate) fn new() -> Self {
+        Self {
+            events: [Event::Hold(Ms(0)); MAX_EVENTS_PER_UPDATE],
+            len: 0,
+        }
+    }
+
+    /// Adds an event, an update never emitting more than [`MAX_EVENTS_PER_UPDATE`].
+    pub(crate) fn push(&mut self, event: Event) {
+        debug_assert!(self.len < MAX_EVENTS_PER_UPDATE, "more events than an update can emit");
+        if let Some(slot) = self.events.get_mut(self.len) {
+            *slot = event;
+            self.len += 1;
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = Event> + '_ {
+        self.events[..self.len].iter().copied()
+    }
+}
+
+/// What a button update works with.
+pub(crate) struct Update<'u> {
+    pub(crate) id: Id,
+    pub(crate) now: Ms,
+    pub(crate) config: ButtonConfig,
+    pub(crate) events: &'u mut Emitted,
+}
+
+/// State machine of a single button, which debounces its presses and turns them into events.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Button {
+    state: State,
+    press_start_timestamp: Option<Ms>,
+    last_hold_event_timestamp: Option<Ms>,
+    last_press_event_sent: Option<Event>,
+    /// Whether a debounced press was released before its deferred event could be sent.
+    has_deferred_press: bool,
+    consecutive_press_count: u8,
+    repeat_count: u16,
+    /// Time from the start of the press to the next auto-repeat, if enabled.
+    next_repeat: Option<Ms>,
+}
+
+impl Button {
+    pub(crate) const fn new() -> Self {
+        Self {
+            state: State::Released,
+            press_start_timestamp: None,
+            last_hold_event_timestamp: None,
+            last_press_event_sent: None,
+            has_deferred_press: false,
+            consecutive_press_count: 0,
+            repeat_count: 0,
+            next_repeat: None,
+        }
+    }
+
+    /// Updates the button, `pressed_since` being the time the current press started if pressed.
+    ///
+    /// The press may have started before the update, in which case the press is handled as if
+    /// the button had been updated at that time.
+    pub(crate) fn update(&mut self, cx: &mut Update<'_>, pressed_since: Option<Ms>) {
+        let pressed = pressed_since.is_some();
+        self.state = match self.state {
+            State::Released => self.released_state(cx, pressed_since),
+            State::Debouncing => self.debouncing_state(cx, pressed),
+            State::Pressed => self.pressed_state(cx, pressed),
+        };
+    }
+
+    fn released_state(&mut self, cx: &mut Update<'_>, pressed_since: Option<Ms>) -> State {
+        let Some(pressed_since) = pressed_since else {
+            // We had one press but didn't send any events because the press was released too quickly
+            // before we could determine if the repeated presses had ended
+            if let (true, Some(press_start_timestamp)) = (self.has_deferred_press, self.press_start_timestamp) {
+                let time_since_last_press_started = cx.now.elapsed_since(press_start_timestamp);
+
+                if self.consecutive_press_count > 0
+                    && time_since_last_press_started > cx.config.repeated_press_threshold_duration
+                {
+                    self.has_deferred_press = false;
+
+                    // The first press event counts as the reference time to start sending hold events
+                    self.last_hold_event_timestamp = Some(cx.now);
+
+                    let kind = self.press_kind(Length::Short);
+                    self.last_press_event_sent = Some(Event::Press(kind));
+                    cx.events.push(Event::Press(kind));
+                    // The press is already over
+                    cx.events.push(Event::Release(kind));
+                }
+            }
+            return State::Released;
+        };
+
+        debug!("Button {} pressed", cx.id);
+        if let Some(press_start_timestamp) = self.press_start_timestamp {
+            let time_since_last_press = pressed_since.elapsed_since(press_start_timestamp);
+
+            if !cx.config.repeated_press_support || time_since_last_press > cx.config.repeated_press_threshold_duration
+            {
+                trace!("Consecutive count reset");
+                self.consecutive_press_count = 0;
+                self.has_deferred_press = false;
+            }
+        }
+
+        self.press_start_timestamp = Some(pressed_since);
+        self.start_press();
+        self.next_repeat = cx.config.auto_repeat.map(|auto_repeat| auto_repeat.initial_delay);
+
+        if cx.config.enable_raw_press_release_events {
+            cx.events.push(Event::Press(Kind::Raw));
+        }
+
+        if cx.config.short_press_duration > Ms(0) {
+            // The press may have started long enough ago to be debounced already
+            self.debouncing_state(cx, true)
+        } else {
+            // Call the press state immediately because we want to start handling the press
+            self.pressed_state(cx, true)
+        }
+    }
+
+    fn debouncing_state(&mut self, cx: &mut Update<'_>, pressed: bool) -> State {
+        if !pressed {
+            debug!("Button {} released", cx.id);
+
+            if cx.config.enable_raw_press_release_events {
+                cx.events.push(Event::Release(Kind::Raw));
+            }
+
+            // Presses shorter than the debouncing time are not presses
+            self.consecutive_press_count = self.consecutive_press_count.saturating_sub(1);
+            return State::Released;
+        }
+
+        let Some(press_start_timestamp) = self.press_start_timestamp else {
+            return State::Released;
+        };
+
+        if cx.now.elapsed_since(press_start_timestamp) > cx.config.short_press_duration {
+            // Call the press state immediately because we want to start handling the press
+            return self.pressed_state(cx, true);
+        }
+
+        State::Debouncing
+    }
+
+    fn pressed_state(&mut self, cx: &mut Update<'_>, pressed: bool) -> State {
+        if !pressed {
+            debug!("Button {} press released", cx.id);
+
+            if cx.config.enable_raw_press_release_events {
+                cx.events.push(Event::Release(Kind::Raw));
+            }
+
+            // Only send the press released event if we actually got to send a press event before the button was released
+            if let Some(release_event) = self.last_press_event_sent.and_then(Event::into_release) {
+                cx.events.push(release_event);
+            }
+
+            return State::Released;
+        }
+
+        let Some(press_start_timestamp) = self.press_start_timestamp else {
+            return State::Released;
+        };
+        let time_since_press_started = cx.now.elapsed_since(press_start_timestamp);
+
+        let length = if time_since_press_started >= cx.config.very_long_press_duration {
+            Length::VeryLong
+        } else if time_since_press_started >= cx.config.long_press_duration {
+            Length::Long
+        } else if time_since_press_started >= cx.config.medium_press_duration {
+            Length::Medium
+        } else {
+            Length::Short
+        };
+
+        // Check if enough time has passed to determine whether this is a normal or repeated press
+        let can_send_repeat_press = cx.config.repeated_press_mode == RepeatedPressMode::Immediate
+            || time_since_press_started > cx.config.repeated_press_threshold_duration;
+
+        if !cx.config.repeated_press_support || can_send_repeat_press {
+            let event = if let Some(last_press_event_sent) = self.last_press_event_sent {
+                last_press_event_sent.with_length(length)
+            } else {
+                // The first press event counts as the reference time to start sending hold events
+                self.last_hold_event_timestamp = Some(cx.now);
+                Event::Press(self.press_kind(length))
+            };
+
+            if Some(event) != self.last_press_event_sent {
+                self.last_press_event_sent = Some(event);
+                cx.events.push(event);
+            }
+            self.has_deferred_press = false;
+        } else {
+            // Sent once released if no other press follows in time
+            self.has_deferred_press = true;
+        }
+
+        // Repeats only follow the press event, which may be deferred
+        if let (Some(auto_repeat), Some(next_repeat), Some(_)) =
+            (cx.config.auto_repeat, self.next_repeat, self.last_press_event_sent)
+        {
+            if time_since_press_started >= next_repeat {
+                self.repeat_count = self.repeat_count.saturating_add(1);
+                self.next_repeat = Some(next_repeat + auto_repeat.interval_after(self.repeat_count));
+                cx.events.push(Event::Repeat(self.repeat_count));
+            }
+        }
+
+        if let Some(last_hold_event_timestamp) = self.last_hold_event_timestamp {
+            let time_since_last_hold_event = cx.now.elapsed_since(last_hold_event_timestamp);
+            if time_since_last_hold_event > cx.config.hold_event_interval {
+                cx.events.push(Event::Hold(time_since_press_started));
+                self.last_hold_event_timestamp = Some(cx.now);
+            }
+        }
+
+        State::Pressed
+    }
+
+    fn start_press(&mut self) {
+        self.last_press_event_sent = None;
+        self.last_hold_event_timestamp = None;
+        self.repeat_count = 0;
+        self.consecutive_press_count = self.consecutive_press_count.saturating_add(1);
+    }
+
+    fn press_kind(&self, length: Length) -> Kind {
+        match self.consecutive_press_count {
+            // A press always counts, this is only a safe fallback
+            0 | 1 => Kind::Single(length),
+            2 => Kind::Double(length),
+            3 => Kind::Triple(length),
+            n => Kind::Repeated(length, n),
+        }
+    }
+
+    /// Gets the next time the button must be updated at even if its input does not change, if any.
+    ///
+    /// `now` is the time of the last update.
+    pub(crate) fn next_deadline(&self, config: &ButtonConfig, now: Ms) -> Option<Ms> {
+        let press_start_timestamp = self.press_start_timestamp?;
+        // Timeouts are checked with strict comparisons, hence the extra millisecond
+        let after = |duration: Ms| press_start_timestamp + duration + Ms(1);
+
+        match self.state {
+            State::Released => {
+                let is_press_pending = self.has_deferred_press && self.consecutive_press_count > 0;
+                is_press_pending.then(|| after(config.repeated_press_threshold_duration))
+            }
+            State::Debouncing => Some(after(config.short_press_duration)),
+            State::Pressed => {
+                let is_press_deferred = self.last_press_event_sent.is_none()
+                    && config.repeated_press_support
+                    && config.repeated_press_mode == RepeatedPressMode::Deferred;
+                let deferred_press = is_press_deferred.then(|| after(config.repeated_press_threshold_duration));
+
+                // The length of the press only matters once its event is sent
+                let time_since_press_started = now.elapsed_since(press_start_timestamp);
+                let next_length = [
+                    config.medium_press_duration,
+                    config.long_press_duration,
+                    config.very_long_press_duration,
+                ]
+                .into_iter()
+                .filter(|&duration| duration > time_since_press_started)
+                .min()
+                .filter(|_| !is_press_deferred)
+                .map(|duration| press_start_timestamp + duration);
+
+                let next_hold = self
+                    .last_hold_event_timestamp
+                    .map(|timestamp| timestamp + config.hold_event_interval + Ms(1));
+
+                // Repeats falling behind are sent one per update, as soon as possible
+                let next_repeat = self
+                    .next_repeat
+                    .filter(|_| config.auto_repeat.is_some() && self.last_press_event_sent.is_some())
+                    .map(|next_repeat| press_start_timestamp + next_repeat.max(time_since_press_started));
+
+                [deferred_press, next_length, next_hold, next_repeat]
+                    .into_iter()
+                    .flatten()
+                    .min_by_key(|deadline| deadline.elapsed_since(now))
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{Id, IdSet, Ms};
+
+/// Buttons pressed together acting as a button of their own.
+///
+/// The chord is handled as a button with the [`Id`] `id`, which must not be the [`Id`] of a real
+/// button, and gets the same events a real button does. The buttons making up the chord must be
+/// pressed within [`Config::chord_window`](crate::Config::chord_window) of each other, their own
+/// events being suppressed until they are released.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Chord {
+    pub id: Id,
+    pub buttons: IdSet,
+}
+
+impl Chord {
+    pub const fn new(id: Id, buttons: IdSet) -> Self {
+        Self { id, buttons }
+    }
+}
+
+/// Most chords that can be configured, later ones are ignored.
+const MAX_CHORDS: usize = 32;
+
+/// Turns the buttons pressed into the buttons seen by the state machines, chords included.
+///
+/// Presses of the buttons that are part of a chord are held back for the chord window, waiting
+/// for the other buttons of the chord. If the chord is not completed in time, the press is
+/// forwarded as if it had just started when the button was pressed.
+pub(crate) struct ChordTracker<const N: usize> {
+    /// Buttons pressed at the last update.
+    previous: IdSet,
+    /// Time every button, chords included, was last pressed.
+    press_timestamps: [Ms; N],
+    /// Buttons held back, waiting for a chord.
+    pending: IdSet,
+    /// Buttons that made up a chord, ignored until they are released.
+    suppressed: IdSet,
+    /// Chords currently pressed, one bit per chord of the configuration.
+    active: u32,
+}
+
+impl<const N: usize> ChordTracker<N> {
+    pub(crate) fn new() -> Self {
+        Self {
+            previous: IdSet::empty(),
+            press_timestamps: [Ms(0); N],
+            pending: IdSet::empty(),
+            suppressed: IdSet::empty(),
+            active: 0,
+        }
+    }
+
+    /// Gets the time a button seen pressed by [`ChordTracker::update`] was pressed.
+    pub(crate) fn press_timestamp(&self, id: Id) -> Ms {
+        self.press_timestamps.get(id.0).copied().unwrap_or(Ms(0))
+    }
+
+    /// Gets the buttons to feed the state machines with, from the buttons actually pressed.
+    pub(crate) fn update(&mut self, chords: &[Chord], window: Ms, pressed: IdSet, now: Ms) -> IdSet {
+        let chords = &chords[..chords.len().min(MAX_CHORDS)];
+
+        let newly_pressed = pressed & !self.previous;
+        self.previous = pressed;
+        for id in newly_pressed.iter() {
+            self.set_press_timestamp(id, now);
+        }
+
+        // Buttons of a chord stay ignored until released, even once the chord is released
+        self.suppressed = self.suppressed & pressed;
+        for (i, chord) in chords.iter().enumerate() {
+            if !pressed.is_superset(chord.buttons) {
+                self.active &= !(1 << i);
+            }
+        }
+
+        let members = chords.iter().fold(IdSet::empty(), |members, chord| members | chord.buttons);
+        self.pending = self.pending | (newly_pressed & members);
+
+        // Earlier chords take precedence when several are completed at once
+        for (i, chord) in chords.iter().enumerate() {
+            let is_completed = !chord.buttons.is_empty()
+                && self.pending.is_superset(chord.buttons)
+                && pressed.is_superset(chord.buttons);
+
+            if is_completed {
+                debug!("Chord {} pressed", chord.id);
+                self.active |= 1 << i;
+                self.suppressed = self.suppressed | chord.buttons;
+                self.pending = self.pending & !chord.buttons;
+                self.set_press_timestamp(chord.id, now);
+            }
+        }
+
+        // Buttons released or held for too long while waiting are forwarded
+        let mut released = IdSet::empty();
+        for id in self.pending.iter() {
+            if !pressed.contains(id) {
+                released.insert(id);
+                self.pending.remove(id);
+            } else if now.elapsed_since(self.press_timestamp(id)) > window {
+                self.pending.remove(id);
+            }
+        }
+
+        // Buttons released while waiting are seen pressed once, for their press not to be lost
+        let mut forwarded = (pressed & !self.pending & !self.suppressed) | released;
+        for (i, chord) in chords.iter().enumerate() {
+            if self.active & (1 << i) != 0 {
+                forwarded.insert(chord.id);
+            }
+        }
+        forwarded
+    }
+
+    /// Gets the next time a button waiting for a chord must be forwarded, if any.
+    pub(crate) fn next_deadline(&self, window: Ms, now: Ms) -> Option<Ms> {
+        self.pending
+            .iter()
+            .map(|id| self.press_timestamp(id) + window + Ms(1))
+            .min_by_key(|deadline| deadline.elapsed_since(now))
+    }
+
+    fn set_press_timestamp(&mut self, id: Id, timestamp: Ms) {
+        if let Some(press_timestamp) = self.press_timestamps.get_mut(id.0) {
+            *press_timestamp = timestamp;
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use core::cell::Cell;
+
+use crate::Ms;
+
+/// Source of the current time for [`Buttons`](crate::Buttons).
+///
+/// The time must use the whole range of [`Ms`], wrapping around from `u64::MAX` to 0, for the
+/// durations between timestamps to stay right across the wraparound.
+pub trait Clock {
+    fn now(&self) -> Ms;
+}
+
+impl<C: Clock> Clock for &C {
+    fn now(&self) -> Ms {
+        (**self).now()
+    }
+}
+
+/// Time since boot, as counted by `embassy-time`.
+#[cfg(feature = "embassy-time")]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct EmbassyClock;
+
+#[cfg(feature = "embassy-time")]
+impl Clock for EmbassyClock {
+    fn now(&self) -> Ms {
+        Ms(embassy_time::Instant::now().as_millis())
+    }
+}
+
+/// Clock only moving when told to, e.g. for tests.
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now: Cell<Ms>,
+}
+
+impl ManualClock {
+    pub const fn new(now: Ms) -> Self {
+        Self { now: Cell::new(now) }
+    }
+
+    pub fn set(&self, now: Ms) {
+        self.now.set(now);
+    }
+
+    /// Moves the time forward, wrapping around past `u64::MAX`.
+    pub fn advance(&self, duration: Ms) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Ms {
+        self.now.get()
+    }
+}
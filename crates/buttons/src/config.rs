@@ -1,4 +1,4 @@
-use crate::{Id, Ms};
+use crate::{Chord, Gesture, Id, Ms};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum RepeatedPressMode {
@@ -16,4 +16,163 @@ pub struct Config<'a> {
     pub buttons_with_repeated_press_support: Option<&'a [Id]>,
     pub repeated_press_mode: RepeatedPressMode,
     pub enable_raw_press_release_events: bool,
+    /// Sends [`Event::Repeat`](crate::Event::Repeat) while the button is held, if set.
+    pub auto_repeat: Option<AutoRepeat>,
+    /// Combinations of buttons handled as buttons of their own.
+    pub chords: &'a [Chord],
+    /// Longest time between the presses of the first and last buttons of a chord.
+    ///
+    /// The buttons that are part of a chord get their events this much later.
+    pub chord_window: Ms,
+    /// Sequences of presses sent as events of their own, in addition to the events of the presses.
+    pub gestures: &'a [Gesture<'a>],
+    /// Settings of single buttons or chords overriding the ones above, the first match winning.
+    pub overrides: &'a [ButtonOverrides],
+}
+
+impl Config<'static> {
+    /// Configuration without any chord, gesture, auto-repeat or override, to start from:
+    ///
+    /// ```ignore
+    /// const CONFIG: Config<'static> = Config {
+    ///     chords: &CHORDS,
+    ///     chord_window: Ms(80),
+    ///     ..Config::DEFAULT
+    /// };
+    /// ```
+    pub const DEFAULT: Self = Self {
+        short_press_duration: Ms(50),
+        medium_press_duration: Ms(1000),
+        long_press_duration: Ms(5000),
+        very_long_press_duration: Ms(30000),
+        hold_event_interval: Ms(100),
+        repeated_press_threshold_duration: Ms(500),
+        buttons_with_repeated_press_support: None,
+        repeated_press_mode: RepeatedPressMode::Immediate,
+        enable_raw_press_release_events: false,
+        auto_repeat: None,
+        chords: &[],
+        chord_window: Ms(0),
+        gestures: &[],
+        overrides: &[],
+    };
+}
+
+impl Config<'_> {
+    /// Gets the settings of a button, overrides applied.
+    pub fn button(&self, id: Id) -> ButtonConfig {
+        let overrides = self
+            .overrides
+            .iter()
+            .find(|overrides| overrides.id == id)
+            .copied()
+            .unwrap_or(ButtonOverrides::new(id));
+
+        let repeated_press_support = match self.buttons_with_repeated_press_support {
+            Some(buttons) => buttons.contains(&id),
+            // If the user provided no list in the configuration, all buttons support repeated presses by default
+            None => true,
+        };
+
+        ButtonConfig {
+            short_press_duration: overrides.short_press_duration.unwrap_or(self.short_press_duration),
+            medium_press_duration: overrides.medium_press_duration.unwrap_or(self.medium_press_duration),
+            long_press_duration: overrides.long_press_duration.unwrap_or(self.long_press_duration),
+            very_long_press_duration: overrides.very_long_press_duration.unwrap_or(self.very_long_press_duration),
+            hold_event_interval: overrides.hold_event_interval.unwrap_or(self.hold_event_interval),
+            repeated_press_threshold_duration: overrides
+                .repeated_press_threshold_duration
+                .unwrap_or(self.repeated_press_threshold_duration),
+            repeated_press_support: overrides.repeated_press_support.unwrap_or(repeated_press_support),
+            repeated_press_mode: overrides.repeated_press_mode.unwrap_or(self.repeated_press_mode),
+            enable_raw_press_release_events: overrides
+                .enable_raw_press_release_events
+                .unwrap_or(self.enable_raw_press_release_events),
+            auto_repeat: overrides.auto_repeat.unwrap_or(self.auto_repeat),
+        }
+    }
+}
+
+/// Settings of one button or chord replacing the ones of the [`Config`], `None` keeping them.
+///
+/// Meant for constant tables:
+///
+/// ```ignore
+/// const OVERRIDES: [ButtonOverrides; 1] = [ButtonOverrides {
+///     long_press_duration: Some(Ms(2000)),
+///     ..ButtonOverrides::new(POWER_BUTTON)
+/// }];
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ButtonOverrides {
+    pub id: Id,
+    pub short_press_duration: Option<Ms>,
+    pub medium_press_duration: Option<Ms>,
+    pub long_press_duration: Option<Ms>,
+    pub very_long_press_duration: Option<Ms>,
+    pub hold_event_interval: Option<Ms>,
+    pub repeated_press_threshold_duration: Option<Ms>,
+    /// Replaces being part of [`Config::buttons_with_repeated_press_support`].
+    pub repeated_press_support: Option<bool>,
+    pub repeated_press_mode: Option<RepeatedPressMode>,
+    pub enable_raw_press_release_events: Option<bool>,
+    /// `Some(None)` turns auto-repeat off for the button.
+    pub auto_repeat: Option<Option<AutoRepeat>>,
+}
+
+impl ButtonOverrides {
+    /// Overrides nothing.
+    pub const fn new(id: Id) -> Self {
+        Self {
+            id,
+            short_press_duration: None,
+            medium_press_duration: None,
+            long_press_duration: None,
+            very_long_press_duration: None,
+            hold_event_interval: None,
+            repeated_press_threshold_duration: None,
+            repeated_press_support: None,
+            repeated_press_mode: None,
+            enable_raw_press_release_events: None,
+            auto_repeat: None,
+        }
+    }
+}
+
+/// Settings of one button, see [`Config::button`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ButtonConfig {
+    pub short_press_duration: Ms,
+    pub medium_press_duration: Ms,
+    pub long_press_duration: Ms,
+    pub very_long_press_duration: Ms,
+    pub hold_event_interval: Ms,
+    pub repeated_press_threshold_duration: Ms,
+    pub repeated_press_support: bool,
+    pub repeated_press_mode: RepeatedPressMode,
+    pub enable_raw_press_release_events: bool,
+    pub auto_repeat: Option<AutoRepeat>,
+}
+
+/// Repeats sent while a button is held, getting faster the longer it is held, like a keyboard.
+///
+/// The first repeat is sent `initial_delay` after the press started, the second `interval` after
+/// the first, and every following one `acceleration` sooner than the previous one, down to
+/// `min_interval`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AutoRepeat {
+    pub initial_delay: Ms,
+    pub interval: Ms,
+    pub min_interval: Ms,
+    pub acceleration: Ms,
+}
+
+impl AutoRepeat {
+    /// Gets the time between a repeat and the next one, `count` repeats having been sent.
+    pub fn interval_after(&self, count: u16) -> Ms {
+        let speedup = self.acceleration.0.saturating_mul(u64::from(count.saturating_sub(1)));
+        let interval = Ms(self.interval.0.saturating_sub(speedup)).max(self.min_interval);
+        // Repeats are sent at most once per update
+        interval.max(Ms(1))
+    }
 }
@@ -6,6 +6,10 @@ pub enum Event {
     Release(Kind),
     Press(Kind),
     Hold(Ms),
+    /// Auto-repeat of a held button, with the number of repeats sent for the press so far.
+    Repeat(u16),
+    /// Sequence of presses matching the [`Gesture`](crate::Gesture) with this id.
+    Gesture(u8),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -28,39 +32,44 @@ pub enum Length {
 }
 
 impl Event {
-    pub(crate) fn into_release(self) -> Self {
+    /// Gets the release matching a press, `None` for events that are not presses or releases.
+    pub(crate) fn into_release(self) -> Option<Self> {
         match self {
-            Event::Release(_) => self,
-            Event::Press(k) => Event::Release(k),
-            Event::Hold(_) => panic!("Cannot convert `Event::Hold` into `Event::Release`"),
+            Event::Release(_) => Some(self),
+            Event::Press(k) => Some(Event::Release(k)),
+            Event::Hold(_) | Event::Repeat(_) | Event::Gesture(_) => None,
         }
     }
 
+    /// Changes the length of a press or release, other events having no length.
     pub(crate) fn with_length(self, length: Length) -> Self {
         match self {
-            Event::Release(_) => Event::Release(self.kind().with_length(length)),
-            Event::Press(_) => Event::Press(self.kind().with_length(length)),
-            Event::Hold(_) => panic!("Cannot add length to `Event::Hold`"),
+            Event::Release(k) => Event::Release(k.with_length(length)),
+            Event::Press(k) => Event::Press(k.with_length(length)),
+            Event::Hold(_) | Event::Repeat(_) | Event::Gesture(_) => self,
         }
     }
+}
 
-    fn kind(&self) -> Kind {
+impl Kind {
+    /// Gets the length of the press, `None` for raw presses.
+    pub fn length(&self) -> Option<Length> {
         match self {
-            Event::Release(k) => k.clone(),
-            Event::Press(k) => k.clone(),
-            Event::Hold(_) => unreachable!(),
+            Kind::Raw => None,
+            Kind::Single(length) | Kind::Double(length) | Kind::Triple(length) | Kind::Repeated(length, _) => {
+                Some(*length)
+            }
         }
     }
-}
 
-impl Kind {
+    /// Changes the length of the press, raw presses having no length.
     pub(crate) fn with_length(self, length: Length) -> Self {
         match self {
-            Kind::Raw => panic!("Cannot add length to `Kind::Raw`"),
+            Kind::Raw => Kind::Raw,
             Kind::Single(_) => Kind::Single(length),
             Kind::Double(_) => Kind::Double(length),
             Kind::Triple(_) => Kind::Triple(length),
-            Kind::Repeated(_, count) => Kind::Repeated(length, count.clone()),
+            Kind::Repeated(_, count) => Kind::Repeated(length, count),
         }
     }
 }
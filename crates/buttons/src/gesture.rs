@@ -0,0 +1,199 @@
+use crate::button::Emitted;
+use crate::{Event, Id, Length, Ms};
+
+/// Sequence of presses of one button, e.g. short-short-long, sent as [`Event::Gesture`].
+///
+/// The presses are matched on the [`Event::Press`] and [`Event::Release`] events of the button,
+/// so its presses should not be deferred by repeated press detection. Every press must start at
+/// most `max_gap` after the previous one was released, the time it takes to debounce it or to
+/// wait for a chord not counting.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Gesture<'a> {
+    /// Sent with [`Event::Gesture`].
+    pub id: u8,
+    pub button: Id,
+    /// Lengths of the presses, at most [`Gesture::MAX_PRESSES`].
+    pub presses: &'a [Length],
+    pub max_gap: Ms,
+}
+
+impl<'a> Gesture<'a> {
+    /// Longest sequence of presses that can be matched, longer gestures are ignored.
+    pub const MAX_PRESSES: usize = 8;
+
+    pub const fn new(id: u8, button: Id, presses: &'a [Length], max_gap: Ms) -> Self {
+        Self {
+            id,
+            button,
+            presses,
+            max_gap,
+        }
+    }
+}
+
+/// Presses of a button matching the start of at least one gesture.
+#[derive(Debug, Copy, Clone)]
+struct Sequence {
+    presses: [Length; Gesture::MAX_PRESSES],
+    len: usize,
+    /// A press event was sent, the sequence waits for its release.
+    is_pressed: bool,
+    /// Time the button was pressed at if it is held, its press event possibly not sent yet.
+    held_since: Option<Ms>,
+    last_release_timestamp: Ms,
+}
+
+impl Sequence {
+    const fn new() -> Self {
+        Self {
+            presses: [Length::Short; Gesture::MAX_PRESSES],
+            len: 0,
+            is_pressed: false,
+            held_since: None,
+            last_release_timestamp: Ms(0),
+        }
+    }
+
+    fn presses(&self) -> &[Length] {
+        &self.presses[..self.len]
+    }
+
+    /// Adds a press, dropping the first one if the sequence is full.
+    fn push(&mut self, length: Length) {
+        if self.len == Gesture::MAX_PRESSES {
+            self.drop_first();
+        }
+        if let Some(slot) = self.presses.get_mut(self.len) {
+            *slot = length;
+            self.len += 1;
+        }
+    }
+
+    fn drop_first(&mut self) {
+        if self.len > 0 {
+            self.presses.copy_within(1..self.len, 0);
+            self.len -= 1;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+/// Matches the events of every button against the gestures of the configuration.
+///
+/// A sequence matching a gesture is sent right away, unless it is also the start of a longer
+/// gesture, in which case it is only sent once the gap to the next press times out.
+pub(crate) struct GestureTracker<const N: usize> {
+    sequences: [Sequence; N],
+}
+
+impl<const N: usize> GestureTracker<N> {
+    pub(crate) fn new() -> Self {
+        Self {
+            sequences: [Sequence::new(); N],
+        }
+    }
+
+    /// Ends the sequence of a button if its next press is late, sending the gesture it matches.
+    ///
+    /// `held_since` is the time the button was pressed at if it is held, before its press event
+    /// is sent.
+    pub(crate) fn expire(
+        &mut self,
+        gestures: &[Gesture<'_>],
+        id: Id,
+        held_since: Option<Ms>,
+        now: Ms,
+        events: &mut Emitted,
+    ) {
+        let Some(sequence) = self.sequences.get_mut(id.0) else {
+            return;
+        };
+
+        sequence.held_since = held_since;
+        if sequence.len == 0 || sequence.is_pressed {
+            return;
+        }
+
+        // A press started in time continues the sequence, even while its press event is delayed
+        let gap = held_since.unwrap_or(now).elapsed_since(sequence.last_release_timestamp);
+        let is_late = max_gap(gestures, id, sequence.presses())
+            .filter(|&max_gap| gap <= max_gap)
+            .is_none();
+        if is_late {
+            if let Some(gesture) = find(gestures, id, sequence.presses()) {
+                debug!("Gesture {} on button {}", gesture.id, id);
+                events.push(Event::Gesture(gesture.id));
+            }
+            sequence.clear();
+        }
+    }
+
+    /// Follows an event sent for a button, sending the gesture it completes, if any.
+    pub(crate) fn update(&mut self, gestures: &[Gesture<'_>], id: Id, event: Event, now: Ms, events: &mut Emitted) {
+        let Some(sequence) = self.sequences.get_mut(id.0) else {
+            return;
+        };
+
+        let length = match event {
+            Event::Press(kind) if kind.length().is_some() => {
+                sequence.is_pressed = true;
+                return;
+            }
+            Event::Release(kind) => match kind.length() {
+                Some(length) => length,
+                None => return,
+            },
+            _ => return,
+        };
+
+        sequence.is_pressed = false;
+        sequence.last_release_timestamp = now;
+        sequence.push(length);
+
+        // Earlier presses are dropped until the sequence is the start of a gesture again
+        while sequence.len > 0 && max_gap(gestures, id, sequence.presses()).is_none() {
+            if let Some(gesture) = find(gestures, id, sequence.presses()) {
+                debug!("Gesture {} on button {}", gesture.id, id);
+                events.push(Event::Gesture(gesture.id));
+                sequence.clear();
+                return;
+            }
+            sequence.drop_first();
+        }
+    }
+
+    /// Gets the next time a sequence waiting for its next press must be ended, if any.
+    pub(crate) fn next_deadline(&self, gestures: &[Gesture<'_>], now: Ms) -> Option<Ms> {
+        self.sequences
+            .iter()
+            .enumerate()
+            .filter(|(_, sequence)| sequence.len > 0 && !sequence.is_pressed && sequence.held_since.is_none())
+            .filter_map(|(i, sequence)| {
+                let max_gap = max_gap(gestures, Id(i), sequence.presses())?;
+                Some(sequence.last_release_timestamp + max_gap + Ms(1))
+            })
+            .min_by_key(|deadline| deadline.elapsed_since(now))
+    }
+}
+
+/// Gets the first gesture of a button made of exactly these presses.
+fn find<'g, 'a>(gestures: &'g [Gesture<'a>], id: Id, presses: &[Length]) -> Option<&'g Gesture<'a>> {
+    gestures
+        .iter()
+        .find(|gesture| gesture.button == id && gesture.presses == presses)
+}
+
+/// Gets the longest gap allowed before the next press by the gestures of a button starting with
+/// these presses, if any.
+fn max_gap(gestures: &[Gesture<'_>], id: Id, presses: &[Length]) -> Option<Ms> {
+    gestures
+        .iter()
+        .filter(|gesture| {
+            gesture.button == id && gesture.presses.len() > presses.len() && gesture.presses.starts_with(presses)
+        })
+        .map(|gesture| gesture.max_gap)
+        .max()
+}
@@ -0,0 +1,108 @@
+use core::ops::{BitAnd, BitOr, Not};
+
+use crate::Id;
+
+/// Set of buttons, one bit per [`Id`], e.g. the buttons currently pressed.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct IdSet(pub u32);
+
+impl IdSet {
+    /// Highest number of buttons in a set.
+    pub const CAPACITY: usize = 32;
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Adds a button to the set, for use in constants.
+    pub const fn with(self, id: Id) -> Self {
+        Self(self.0 | Self::bit(id))
+    }
+
+    pub const fn contains(&self, id: Id) -> bool {
+        self.0 & Self::bit(id) != 0
+    }
+
+    pub fn insert(&mut self, id: Id) {
+        self.0 |= Self::bit(id);
+    }
+
+    pub fn remove(&mut self, id: Id) {
+        self.0 &= !Self::bit(id);
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Checks if every button of `other` is in the set.
+    pub const fn is_superset(&self, other: IdSet) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Iterates over the buttons of the set, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = Id> {
+        let bits = self.0;
+        (0..Self::CAPACITY).filter(move |&i| bits & (1 << i) != 0).map(Id)
+    }
+
+    /// Buttons past the capacity of the set are never part of it.
+    const fn bit(id: Id) -> u32 {
+        if id.0 < Self::CAPACITY {
+            1 << id.0
+        } else {
+            0
+        }
+    }
+}
+
+impl From<Id> for IdSet {
+    fn from(id: Id) -> Self {
+        Self::empty().with(id)
+    }
+}
+
+impl From<Option<Id>> for IdSet {
+    fn from(id: Option<Id>) -> Self {
+        id.map(Self::from).unwrap_or_default()
+    }
+}
+
+impl FromIterator<Id> for IdSet {
+    fn from_iter<I: IntoIterator<Item = Id>>(iter: I) -> Self {
+        let mut set = Self::empty();
+        for id in iter {
+            set.insert(id);
+        }
+        set
+    }
+}
+
+impl BitOr for IdSet {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for IdSet {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl Not for IdSet {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self(!self.0)
+    }
+}
@@ -2,9 +2,11 @@
 #![feature(type_alias_impl_trait)]
 #![feature(async_fn_in_trait)]
 
-use core::marker::PhantomData;
 use core::ops::{Add, Sub};
 
+mod clock;
+pub use clock::*;
+
 mod config;
 pub use config::*;
 
@@ -14,273 +16,155 @@ pub use event::*;
 mod fmt;
 pub use fmt::*;
 
-enum State {
-    Released,
-    Debouncing,
-    Pressed,
-}
-
-pub struct Buttons<'a, T: Handler> {
-    handler: PhantomData<T>,
+mod button;
+use button::{Button, Emitted, Update};
+
+mod id_set;
+pub use id_set::*;
+
+mod chord;
+use chord::ChordTracker;
+pub use chord::Chord;
+
+mod gesture;
+use gesture::GestureTracker;
+pub use gesture::Gesture;
+
+/// Debounces the presses of up to `N` buttons and turns them into [`Event`]s.
+///
+/// Every button has its own state machine, so presses of different buttons overlapping in time
+/// produce independent streams of events: holding one button while tapping another one does not
+/// release the first.
+///
+/// Updates are synchronous and return the events emitted, see [`Events::dispatch`] to forward them
+/// to an async [`Handler`].
+pub struct Buttons<'a, C: Clock, const N: usize> {
     config: Config<'a>,
-    state: State,
-    last_button_pressed: Option<Id>,
-    last_press_start_timestamp: Option<Ms>,
-    last_hold_event_timestamp: Option<Ms>,
-    last_press_event_sent: Option<Event>,
-    consecutive_press_count: u8,
+    clock: C,
+    chords: ChordTracker<N>,
+    gestures: GestureTracker<N>,
+    buttons: [Button; N],
+    /// Time of the last update.
+    now: Ms,
 }
 
-impl<'a, T: Handler> Buttons<'a, T> {
-    pub fn new(config: Config<'a>) -> Self {
+impl<'a, C: Clock, const N: usize> Buttons<'a, C, N> {
+    /// Every button must fit in an [`IdSet`].
+    const MAX_BUTTONS: () = assert!(N <= IdSet::CAPACITY, "at most 32 buttons are supported");
+
+    pub fn new(config: Config<'a>, clock: C) -> Self {
+        let () = Self::MAX_BUTTONS;
+
         Self {
-            handler: PhantomData,
             config,
-            state: State::Released,
-            last_button_pressed: None,
-            last_press_start_timestamp: None,
-            last_press_event_sent: None,
-            last_hold_event_timestamp: None,
-            consecutive_press_count: 0,
+            clock,
+            chords: ChordTracker::new(),
+            gestures: GestureTracker::new(),
+            buttons: [Button::new(); N],
+            now: Ms(0),
         }
     }
 
-    pub async fn process_input(&mut self, handler: &mut T, input: Option<Id>) {
-        self.state = match self.state {
-            State::Released => self.released_state(handler, input).await,
-            State::Debouncing => self.debouncing_state(handler, input).await,
-            State::Pressed => self.pressed_state(handler, input).await,
-        };
+    pub fn clock(&self) -> &C {
+        &self.clock
     }
 
-    async fn released_state(&mut self, handler: &mut T, input: Option<Id>) -> State {
-        let Some(input) = input else {
-            // We had one press but didn't send any events because the press was released too quickly
-            // before we could determine if the repeated presses had ended
-            if self.last_press_event_sent.is_none() {
-                let current_time = handler.get_current_timestamp();
-                let time_since_last_press_started = current_time.elapsed_since(self.last_press_start_timestamp.unwrap());
-
-                if time_since_last_press_started > self.config.repeated_press_threshold_duration {
-                    let event = if let Some(last_press_event_sent) = self.last_press_event_sent {
-                        last_press_event_sent.with_length(Length::Short)
-                    } else {
-                        // The first press event counts as the reference time to start sending hold events
-                        self.last_hold_event_timestamp = Some(handler.get_current_timestamp());
-
-                        match self.consecutive_press_count {
-                            0 => unreachable!(),
-                            1 => Event::Press(Kind::Single(Length::Short)),
-                            2 => Event::Press(Kind::Double(Length::Short)),
-                            3 => Event::Press(Kind::Triple(Length::Short)),
-                            n => Event::Press(Kind::Repeated(Length::Short, n)),
-                        }
-                    };
-
-                    if Some(event) != self.last_press_event_sent {
-                        self.last_press_event_sent = Some(event);
-                        handler.on_event(self.last_button_pressed.unwrap(), event).await;
-                    }
-                }
-            }
-            return State::Released;
+    /// Updates every button from the set of buttons currently pressed.
+    ///
+    /// Buttons and chords with an [`Id`] of `N` or above are ignored.
+    pub fn process_input(&mut self, pressed: IdSet) -> Events<N> {
+        let now = self.clock.now();
+        self.now = now;
+        let input = pressed;
+        let pressed = self.chords.update(self.config.chords, self.config.chord_window, input, now);
+
+        let mut events = Events {
+            emitted: [Emitted::new(); N],
         };
+        for ((i, button), emitted) in self.buttons.iter_mut().enumerate().zip(&mut events.emitted) {
+            let id = Id(i);
+            // A gesture timing out comes before the press after it, which counts from the time
+            // the button is pressed even if debouncing or a chord window hold it back
+            let held_since = (input | pressed).contains(id).then(|| self.chords.press_timestamp(id));
+            self.gestures.expire(self.config.gestures, id, held_since, now, emitted);
+
+            let mut button_events = Emitted::new();
+            let mut cx = Update {
+                id,
+                now,
+                config: self.config.button(id),
+                events: &mut button_events,
+            };
+            let pressed_since = pressed.contains(id).then(|| self.chords.press_timestamp(id));
+            button.update(&mut cx, pressed_since);
 
-        debug!("Button pressed {}", input);
-        if let Some(last_press_start_timestamp) = self.last_press_start_timestamp {
-            let current_time = handler.get_current_timestamp();
-            let time_since_last_press = current_time.elapsed_since(last_press_start_timestamp);
-
-            if Some(input) != self.last_button_pressed
-                || !self.input_supports_repeated_press_detection(input)
-                || time_since_last_press > self.config.repeated_press_threshold_duration
-            {
-                trace!("Consecutive count reset");
-                self.consecutive_press_count = 0;
+            for event in button_events.iter() {
+                emitted.push(event);
+                self.gestures.update(self.config.gestures, id, event, now, emitted);
             }
         }
-
-        self.last_press_start_timestamp = Some(handler.get_current_timestamp());
-        self.start_press(input);
-
-        if self.config.enable_raw_press_release_events {
-            handler.on_event(input, Event::Press(Kind::Raw)).await;
-        }
-
-        if self.config.short_press_duration > Ms(0) {
-            return State::Debouncing;
-        } else {
-            // Call the press state immediately because we want to start handling the press
-            return self.pressed_state(handler, Some(input)).await;
-        }
+        events
     }
 
-    async fn debouncing_state(&mut self, handler: &mut T, input: Option<Id>) -> State {
-        let Some(input) = input else {
-            debug!("Button released");
-
-            if self.config.enable_raw_press_release_events {
-                handler.on_event(self.last_button_pressed.unwrap(), Event::Release(Kind::Raw)).await;
-            }
-
-            return State::Released;
-        };
-
-        let current_time = handler.get_current_timestamp();
-
-        // If the input changes mid-press, reset the press timestamp
-        if Some(input) != self.last_button_pressed {
-            self.last_press_start_timestamp = Some(current_time);
-        }
-
-        let time_since_press_started =
-            current_time.elapsed_since(self.last_press_start_timestamp.unwrap());
-
-        if time_since_press_started > self.config.short_press_duration {
-            // Reset the consecutive press count if the last button press does not match the current one
-            // We do that here too in case the button combination changes mid-press without releasing first
-            if Some(input) != self.last_button_pressed {
-                self.consecutive_press_count = 0;
-            }
-
-            self.start_press(input);
-
-            // Call the press state immediately because we want to start handling the press
-            return self.pressed_state(handler, Some(input)).await;
-        }
-
-        State::Debouncing
+    /// Gets the time at which [`Buttons::process_input`] must be called next if the buttons
+    /// pressed do not change, or `None` if it only needs to be called when they change.
+    ///
+    /// Calling it earlier is harmless, calling it later delays the events of long presses, holds,
+    /// repeats, deferred repeated presses and gestures.
+    pub fn next_deadline(&self) -> Option<Ms> {
+        let buttons = self
+            .buttons
+            .iter()
+            .enumerate()
+            .filter_map(|(i, button)| button.next_deadline(&self.config.button(Id(i)), self.now));
+
+        // Deadlines may wrap around, the earliest one is the closest to now
+        buttons
+            .chain(self.chords.next_deadline(self.config.chord_window, self.now))
+            .chain(self.gestures.next_deadline(self.config.gestures, self.now))
+            .min_by_key(|deadline| deadline.elapsed_since(self.now))
     }
+}
 
-    async fn pressed_state(&mut self, handler: &mut T, input: Option<Id>) -> State {
-        let Some(input) = input else {
-            debug!("{} press released", input);
-
-            if self.config.enable_raw_press_release_events {
-                handler.on_event(self.last_button_pressed.unwrap(), Event::Release(Kind::Raw)).await;
-            }
-
-            // Only send the press released event if we actually got to send a press event before the button was released
-            if let Some(press_event) = &self.last_press_event_sent {
-                handler.on_event(self.last_button_pressed.unwrap(), press_event.into_release()).await;
-            }
-
-            return State::Released;
-        };
-
-        // The button press/combination changed without releasing first
-        if Some(input) != self.last_button_pressed {
-            trace!("Button press changed to {} without releasing first", input);
-
-            if self.config.enable_raw_press_release_events {
-                handler
-                    .on_event(self.last_button_pressed.unwrap(), Event::Release(Kind::Raw))
-                    .await;
-            }
-
-            // Only send the press released event if we actually got to send a press event before the button was released
-            if let Some(press_event) = &self.last_press_event_sent {
-                handler
-                    .on_event(
-                        self.last_button_pressed.unwrap(),
-                        press_event.into_release(),
-                    )
-                    .await;
-            }
-
-            // The input changed to something else,
-            // therefore it can't be a repeated press
-            self.consecutive_press_count = 0;
-            self.last_press_start_timestamp = Some(handler.get_current_timestamp());
-            self.start_press(input);
-
-            if self.config.short_press_duration > Ms(0) {
-                return State::Debouncing;
-            }
-        }
-
-        let current_time = handler.get_current_timestamp();
-        let time_since_press_started =
-            current_time.elapsed_since(self.last_press_start_timestamp.unwrap());
-
-        // Very long press
-        let length = if time_since_press_started >= self.config.very_long_press_duration {
-            Length::VeryLong
-        } else if time_since_press_started >= self.config.long_press_duration {
-            Length::Long
-        } else if time_since_press_started >= self.config.medium_press_duration {
-            Length::Medium
-        } else {
-            Length::Short
-        };
-
-        // Check if enough time has passed to determine whether this is a normal or repeated press
-        let can_send_repeat_press = self.config.repeated_press_mode == RepeatedPressMode::Immediate
-            || time_since_press_started > self.config.repeated_press_threshold_duration;
-
-        if !self.input_supports_repeated_press_detection(input) || can_send_repeat_press {
-            let event = if let Some(last_press_event_sent) = self.last_press_event_sent {
-                last_press_event_sent.with_length(length)
-            } else {
-                // The first press event counts as the reference time to start sending hold events
-                self.last_hold_event_timestamp = Some(handler.get_current_timestamp());
-
-                match self.consecutive_press_count {
-                    0 => unreachable!(),
-                    1 => Event::Press(Kind::Single(length)),
-                    2 => Event::Press(Kind::Double(length)),
-                    3 => Event::Press(Kind::Triple(length)),
-                    n => Event::Press(Kind::Repeated(length, n)),
-                }
-            };
-
-            if Some(event) != self.last_press_event_sent {
-                self.last_press_event_sent = Some(event);
-                handler.on_event(input, event).await;
-            }
-        }
-
-        if let Some(last_hold_event_timestamp) = self.last_hold_event_timestamp {
-            let current_time = handler.get_current_timestamp();
-            let time_since_last_hold_event = current_time.elapsed_since(last_hold_event_timestamp);
-            if time_since_last_hold_event > self.config.hold_event_interval {
-                let hold_time =
-                    current_time.elapsed_since(self.last_press_start_timestamp.unwrap());
-
-                handler.on_event(input, Event::Hold(hold_time)).await;
-                self.last_hold_event_timestamp = Some(handler.get_current_timestamp());
-            }
-        }
+/// Events emitted by one call to [`Buttons::process_input`], in order for every button, buttons
+/// in increasing [`Id`] order.
+#[derive(Debug, Copy, Clone)]
+pub struct Events<const N: usize> {
+    emitted: [Emitted; N],
+}
 
-        State::Pressed
+impl<const N: usize> Events<N> {
+    pub fn is_empty(&self) -> bool {
+        self.emitted.iter().all(Emitted::is_empty)
     }
 
-    fn start_press(&mut self, input: Id) {
-        self.last_button_pressed = Some(input);
-        self.last_press_event_sent = None;
-        self.last_hold_event_timestamp = None;
-        self.consecutive_press_count += 1;
+    pub fn iter(&self) -> impl Iterator<Item = (Id, Event)> + '_ {
+        self.emitted
+            .iter()
+            .enumerate()
+            .flat_map(|(i, emitted)| emitted.iter().map(move |event| (Id(i), event)))
     }
 
-    fn input_supports_repeated_press_detection(&self, input: Id) -> bool {
-        let Some(buttons_with_repeated_press_support) = self.config.buttons_with_repeated_press_support else {
-            // If the user provided no list in the configuration, all buttons support repeated presses by default
-            return true;
-        };
-
-        buttons_with_repeated_press_support.contains(&input)
+    /// Forwards every event to a handler, in order.
+    pub async fn dispatch<T: Handler>(&self, handler: &mut T) {
+        for (id, event) in self.iter() {
+            handler.on_event(id, event).await;
+        }
     }
 }
 
+/// Receiver of the events of the buttons, see [`Events::dispatch`].
 pub trait Handler {
     async fn on_event(&mut self, button: Id, event: Event);
-
-    fn get_current_timestamp(&self) -> Ms;
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+/// A timestamp or a duration, in milliseconds.
+///
+/// Timestamps wrap around, so they are only compared through the time elapsed between them,
+/// and arithmetic wraps around instead of overflowing.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
-pub struct Ms(pub usize);
+pub struct Ms(pub u64);
 
 impl Ms {
     pub fn elapsed_since(&self, reference: Ms) -> Ms {
@@ -292,7 +176,7 @@ impl Add for Ms {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
-        Self(self.0 + rhs.0)
+        Self(self.0.wrapping_add(rhs.0))
     }
 }
 
@@ -300,7 +184,7 @@ impl Sub for Ms {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        Self(self.0 - rhs.0)
+        Self(self.0.wrapping_sub(rhs.0))
     }
 }
 
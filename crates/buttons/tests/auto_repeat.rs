@@ -0,0 +1,86 @@
+use buttons::{AutoRepeat, ButtonOverrides, Buttons, Config, Event, Id, IdSet, Kind, Length, ManualClock, Ms};
+
+mod common;
+use common::run_timed;
+
+const VOLUME: Id = Id(0);
+const PLAY: Id = Id(1);
+
+const REPEAT: AutoRepeat = AutoRepeat {
+    initial_delay: Ms(400),
+    interval: Ms(200),
+    min_interval: Ms(100),
+    acceleration: Ms(50),
+};
+
+const OVERRIDES: [ButtonOverrides; 1] = [ButtonOverrides {
+    auto_repeat: Some(Some(REPEAT)),
+    ..ButtonOverrides::new(VOLUME)
+}];
+
+const CONFIG: Config<'static> = Config {
+    hold_event_interval: Ms(100_000),
+    overrides: &OVERRIDES,
+    ..Config::DEFAULT
+};
+
+#[test]
+fn intervals_shrink_down_to_the_minimum() {
+    let intervals: Vec<_> = (1..=5).map(|count| REPEAT.interval_after(count).0).collect();
+    assert_eq!(intervals, [200, 150, 100, 100, 100]);
+
+    let no_interval = AutoRepeat {
+        interval: Ms(0),
+        min_interval: Ms(0),
+        ..REPEAT
+    };
+    assert_eq!(no_interval.interval_after(u16::MAX), Ms(1));
+}
+
+#[test]
+fn repeats_accelerate_while_held() {
+    let clock = ManualClock::default();
+    let mut buttons = Buttons::<_, 2>::new(CONFIG, &clock);
+    let mut events = Vec::new();
+
+    // Pressed at 0, debounced at 51
+    run_timed(&mut buttons, &mut events, IdSet::from(VOLUME).with(PLAY), 1000);
+    run_timed(&mut buttons, &mut events, IdSet::empty(), 1100);
+
+    let repeats: Vec<_> = events
+        .iter()
+        .filter_map(|&(at, _, event)| match event {
+            Event::Repeat(count) => Some((at.0, count)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(repeats, [(400, 1), (600, 2), (750, 3), (850, 4), (950, 5)]);
+
+    // Buttons without auto-repeat get no repeats
+    let short = Kind::Single(Length::Short);
+    let play: Vec<_> = events.iter().filter(|&&(_, id, _)| id == PLAY).map(|&(_, _, event)| event).collect();
+    assert_eq!(play, [Event::Press(short), Event::Release(short)]);
+}
+
+#[test]
+fn repeats_restart_with_every_press() {
+    let clock = ManualClock::default();
+    let mut buttons = Buttons::<_, 2>::new(CONFIG, &clock);
+    let mut events = Vec::new();
+
+    run_timed(&mut buttons, &mut events, VOLUME.into(), 700);
+    run_timed(&mut buttons, &mut events, IdSet::empty(), 1000);
+    run_timed(&mut buttons, &mut events, VOLUME.into(), 1500);
+
+    let repeats: Vec<_> = events
+        .iter()
+        .filter_map(|&(at, _, event)| match event {
+            Event::Repeat(count) => Some((at.0, count)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(repeats, [(400, 1), (600, 2), (1400, 1)]);
+
+    // Repeats are part of the deadlines
+    assert_eq!(buttons.next_deadline(), Some(Ms(1600)));
+}
@@ -0,0 +1,93 @@
+#![feature(async_fn_in_trait)]
+#![allow(incomplete_features)]
+
+use buttons::{Buttons, Config, Event, Handler, Id, IdSet, Kind, Length, ManualClock, Ms};
+use embassy_futures::block_on;
+
+mod common;
+use common::run;
+
+const PLUS: Id = Id(1);
+const PLAY: Id = Id(2);
+
+const CONFIG: Config<'static> = Config::DEFAULT;
+
+#[derive(Default)]
+struct Recorder {
+    events: Vec<(Id, Event)>,
+}
+
+impl Handler for Recorder {
+    async fn on_event(&mut self, button: Id, event: Event) {
+        self.events.push((button, event));
+    }
+}
+
+#[test]
+fn debounced_press_is_a_single_press() {
+    let clock = ManualClock::default();
+    let mut buttons = Buttons::<_, 4>::new(CONFIG, &clock);
+    let mut events = Vec::new();
+
+    run(&mut buttons, &mut events, PLAY.into(), 100);
+    run(&mut buttons, &mut events, IdSet::empty(), 200);
+
+    let single = Kind::Single(Length::Short);
+    assert_eq!(events, [(PLAY, Event::Press(single)), (PLAY, Event::Release(single))]);
+}
+
+#[test]
+fn overlapping_presses_are_independent() {
+    let clock = ManualClock::default();
+    let mut buttons = Buttons::<_, 4>::new(
+        Config {
+            hold_event_interval: Ms(10_000),
+            ..CONFIG
+        },
+        &clock,
+    );
+    let mut events = Vec::new();
+
+    // Hold Plus and tap Play in the middle
+    run(&mut buttons, &mut events, PLUS.into(), 200);
+    run(&mut buttons, &mut events, IdSet::from(PLUS).with(PLAY), 400);
+    run(&mut buttons, &mut events, PLUS.into(), 1200);
+    run(&mut buttons, &mut events, IdSet::empty(), 1300);
+
+    let short = Kind::Single(Length::Short);
+    let medium = Kind::Single(Length::Medium);
+    assert_eq!(
+        events,
+        [
+            (PLUS, Event::Press(short)),
+            (PLAY, Event::Press(short)),
+            (PLAY, Event::Release(short)),
+            (PLUS, Event::Press(medium)),
+            (PLUS, Event::Release(medium)),
+        ]
+    );
+}
+
+#[test]
+fn unknown_buttons_are_ignored() {
+    let clock = ManualClock::default();
+    let mut buttons = Buttons::<_, 4>::new(CONFIG, &clock);
+    let mut events = Vec::new();
+
+    run(&mut buttons, &mut events, IdSet::from(Id(4)).with(Id(40)), 200);
+    assert!(events.is_empty());
+}
+
+#[test]
+fn events_can_be_dispatched_to_a_handler() {
+    let clock = ManualClock::default();
+    let mut buttons = Buttons::<_, 4>::new(CONFIG, &clock);
+    let mut recorder = Recorder::default();
+
+    run(&mut buttons, &mut Vec::new(), PLUS.into(), 100);
+    let events = buttons.process_input(IdSet::empty());
+    block_on(events.dispatch(&mut recorder));
+
+    assert_eq!(recorder.events, events.iter().collect::<Vec<_>>());
+    assert_eq!(recorder.events, [(PLUS, Event::Release(Kind::Single(Length::Short)))]);
+}
@@ -0,0 +1,74 @@
+use buttons::{Buttons, Chord, Config, Event, Id, IdSet, Kind, Length, ManualClock, Ms};
+
+mod common;
+use common::run;
+
+const PLUS: Id = Id(0);
+const MINUS: Id = Id(1);
+const PLAY: Id = Id(2);
+const VOLUME_CHORD: Id = Id(3);
+
+const CHORDS: [Chord; 1] = [Chord::new(VOLUME_CHORD, IdSet::empty().with(PLUS).with(MINUS))];
+
+const CONFIG: Config<'static> = Config {
+    hold_event_interval: Ms(100_000),
+    chords: &CHORDS,
+    chord_window: Ms(100),
+    ..Config::DEFAULT
+};
+
+#[test]
+fn chord_replaces_its_buttons() {
+    let clock = ManualClock::default();
+    let mut buttons = Buttons::<_, 4>::new(CONFIG, &clock);
+    let mut events = Vec::new();
+
+    run(&mut buttons, &mut events, PLUS.into(), 60);
+    run(&mut buttons, &mut events, IdSet::from(PLUS).with(MINUS), 6000);
+    // The remaining button of the chord stays silent
+    run(&mut buttons, &mut events, MINUS.into(), 6500);
+    run(&mut buttons, &mut events, IdSet::empty(), 7000);
+
+    let events: Vec<_> = events.iter().map(|&(id, event)| (id.0, event)).collect();
+    assert_eq!(
+        events,
+        [
+            (3, Event::Press(Kind::Single(Length::Short))),
+            (3, Event::Press(Kind::Single(Length::Medium))),
+            (3, Event::Press(Kind::Single(Length::Long))),
+            (3, Event::Release(Kind::Single(Length::Long))),
+        ]
+    );
+}
+
+#[test]
+fn buttons_pressed_alone_are_delayed() {
+    let clock = ManualClock::default();
+    let mut buttons = Buttons::<_, 4>::new(CONFIG, &clock);
+    let mut events = Vec::new();
+
+    // Not part of any chord
+    run(&mut buttons, &mut events, PLAY.into(), 70);
+    assert_eq!(events, [(PLAY, Event::Press(Kind::Single(Length::Short)))]);
+    run(&mut buttons, &mut events, IdSet::empty(), 100);
+    events.clear();
+
+    // Waits for the other button of the chord
+    run(&mut buttons, &mut events, PLUS.into(), 200);
+    assert!(events.is_empty());
+    run(&mut buttons, &mut events, PLUS.into(), 220);
+    assert_eq!(events, [(PLUS, Event::Press(Kind::Single(Length::Short)))]);
+}
+
+#[test]
+fn taps_within_the_window_are_not_lost() {
+    let clock = ManualClock::default();
+    let mut buttons = Buttons::<_, 4>::new(CONFIG, &clock);
+    let mut events = Vec::new();
+
+    run(&mut buttons, &mut events, MINUS.into(), 80);
+    run(&mut buttons, &mut events, IdSet::empty(), 200);
+
+    let single = Kind::Single(Length::Short);
+    assert_eq!(events, [(MINUS, Event::Press(single)), (MINUS, Event::Release(single))]);
+}
@@ -0,0 +1,43 @@
+use buttons::{Buttons, Clock, Config, Event, Id, IdSet, Kind, Length, ManualClock, Ms};
+
+const CONFIG: Config<'static> = Config {
+    hold_event_interval: Ms(100_000),
+    ..Config::DEFAULT
+};
+
+#[test]
+fn timestamps_wrap_around() {
+    assert_eq!(Ms(u64::MAX) + Ms(2), Ms(1));
+    assert_eq!(Ms(1) - Ms(2), Ms(u64::MAX));
+    assert_eq!(Ms(1).elapsed_since(Ms(u64::MAX)), Ms(2));
+
+    let clock = ManualClock::new(Ms(u64::MAX));
+    clock.advance(Ms(10));
+    assert_eq!(clock.now(), Ms(9));
+}
+
+#[test]
+fn press_across_wraparound() {
+    let clock = ManualClock::new(Ms(u64::MAX - 500));
+    let mut buttons = Buttons::<_, 1>::new(CONFIG, &clock);
+    let mut events = Vec::new();
+
+    // Pressed for 1.5 s, the clock wrapping around 0.5 s into the press
+    for _ in 0..150 {
+        events.extend(buttons.process_input(Id(0).into()).iter());
+        clock.advance(Ms(10));
+    }
+    assert_eq!(buttons.next_deadline().map(|deadline| deadline.elapsed_since(clock.now())), Some(Ms(3500)));
+    events.extend(buttons.process_input(IdSet::empty()).iter());
+
+    let short = Kind::Single(Length::Short);
+    let medium = Kind::Single(Length::Medium);
+    assert_eq!(
+        events,
+        [
+            (Id(0), Event::Press(short)),
+            (Id(0), Event::Press(medium)),
+            (Id(0), Event::Release(medium)),
+        ]
+    );
+}
@@ -0,0 +1,41 @@
+//! Helpers shared by the tests, each test file using some of them.
+#![allow(dead_code)]
+
+use buttons::{Buttons, Clock, Event, Id, IdSet, ManualClock, Ms};
+
+/// Feeds the same input every 10 ms up to `until`.
+pub fn run<const N: usize>(
+    buttons: &mut Buttons<'_, &ManualClock, N>,
+    events: &mut Vec<(Id, Event)>,
+    pressed: IdSet,
+    until: u64,
+) {
+    run_every(buttons, Ms(10), pressed, until, |_, id, event| events.push((id, event)));
+}
+
+/// Feeds the same input every millisecond up to `until`, recording the events with their time.
+pub fn run_timed<const N: usize>(
+    buttons: &mut Buttons<'_, &ManualClock, N>,
+    events: &mut Vec<(Ms, Id, Event)>,
+    pressed: IdSet,
+    until: u64,
+) {
+    run_every(buttons, Ms(1), pressed, until, |now, id, event| events.push((now, id, event)));
+}
+
+fn run_every<const N: usize>(
+    buttons: &mut Buttons<'_, &ManualClock, N>,
+    step: Ms,
+    pressed: IdSet,
+    until: u64,
+    mut record: impl FnMut(Ms, Id, Event),
+) {
+    let clock = *buttons.clock();
+    while clock.now() < Ms(until) {
+        let now = clock.now();
+        for (id, event) in buttons.process_input(pressed).iter() {
+            record(now, id, event);
+        }
+        clock.advance(step);
+    }
+}
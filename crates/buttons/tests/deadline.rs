@@ -0,0 +1,95 @@
+use buttons::{AutoRepeat, ButtonOverrides, Buttons, Chord, Clock, Config, Event, Id, IdSet, ManualClock, Ms, RepeatedPressMode};
+
+const CHORDS: [Chord; 1] = [Chord::new(Id(3), IdSet::empty().with(Id(1)).with(Id(2)))];
+
+const OVERRIDES: [ButtonOverrides; 1] = [ButtonOverrides {
+    auto_repeat: Some(Some(AutoRepeat {
+        initial_delay: Ms(500),
+        interval: Ms(400),
+        min_interval: Ms(250),
+        acceleration: Ms(50),
+    })),
+    ..ButtonOverrides::new(Id(0))
+}];
+
+const CONFIG: Config<'static> = Config {
+    long_press_duration: Ms(2000),
+    very_long_press_duration: Ms(4000),
+    hold_event_interval: Ms(300),
+    repeated_press_mode: RepeatedPressMode::Deferred,
+    enable_raw_press_release_events: true,
+    chords: &CHORDS,
+    chord_window: Ms(80),
+    overrides: &OVERRIDES,
+    ..Config::DEFAULT
+};
+
+/// Times at which the buttons pressed change, and the buttons pressed from then on.
+const TIMELINE: [(u64, u32); 10] = [
+    (100, 0b0001),
+    (180, 0b0000),
+    (400, 0b0001),
+    (470, 0b0000),
+    (2000, 0b0001),
+    (7000, 0b0000),
+    (8000, 0b0010),
+    (8050, 0b0110),
+    (9500, 0b0100),
+    (9700, 0b0000),
+];
+
+const END: u64 = 12000;
+
+/// Updates the buttons, recording the events with the time they were emitted at.
+fn process(buttons: &mut Buttons<'_, &ManualClock, 4>, events: &mut Vec<(Ms, Id, Event)>, pressed: IdSet) {
+    let now = buttons.clock().now();
+    events.extend(buttons.process_input(pressed).iter().map(|(id, event)| (now, id, event)));
+}
+
+fn pressed_at(time: Ms) -> IdSet {
+    let change = TIMELINE.iter().rev().find(|&&(at, _)| Ms(at) <= time);
+    IdSet(change.map_or(0, |&(_, pressed)| pressed))
+}
+
+#[test]
+fn deadlines_match_polling() {
+    let polling_clock = ManualClock::default();
+    let mut polled = Buttons::<_, 4>::new(CONFIG, &polling_clock);
+    let mut polling = Vec::new();
+    for now in 0..END {
+        polling_clock.set(Ms(now));
+        process(&mut polled, &mut polling, pressed_at(Ms(now)));
+    }
+
+    let scheduling_clock = ManualClock::default();
+    let mut scheduled = Buttons::<_, 4>::new(CONFIG, &scheduling_clock);
+    let mut scheduling = Vec::new();
+    let mut calls = 0;
+    while scheduling_clock.now() < Ms(END) {
+        let pressed = pressed_at(scheduling_clock.now());
+        process(&mut scheduled, &mut scheduling, pressed);
+        calls += 1;
+
+        // Sleep until the next deadline or input change
+        let now = scheduling_clock.now();
+        let next_change = TIMELINE.iter().map(|&(at, _)| Ms(at)).find(|&at| at > now);
+        let next = [next_change, scheduled.next_deadline(), Some(Ms(END))].into_iter().flatten().min();
+        scheduling_clock.set(next.unwrap());
+    }
+
+    assert!(!polling.is_empty());
+    assert_eq!(scheduling, polling);
+    assert!(calls < 100, "{calls} calls");
+}
+
+#[test]
+fn idle_buttons_have_no_deadline() {
+    let clock = ManualClock::default();
+    let mut buttons = Buttons::<_, 4>::new(CONFIG, &clock);
+
+    buttons.process_input(IdSet::empty());
+    assert_eq!(buttons.next_deadline(), None);
+
+    buttons.process_input(Id(0).into());
+    assert_eq!(buttons.next_deadline(), Some(Ms(51)));
+}
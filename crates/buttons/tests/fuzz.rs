@@ -0,0 +1,279 @@
+//! Random timelines of presses and timestamps fed into `process_input`, checking that nothing
+//! panics and that the events stay consistent.
+//!
+//! The number of timelines can be raised with the `BUTTONS_FUZZ_RUNS` environment variable.
+
+use buttons::{
+    AutoRepeat, ButtonOverrides, Buttons, Chord, Clock, Config, Event, Gesture, Id, IdSet, Kind, Length, ManualClock,
+    Ms, RepeatedPressMode,
+};
+
+const BUTTONS: usize = 8;
+const STEPS: usize = 400;
+const DEFAULT_RUNS: u64 = 500;
+
+const CHORDS: [Chord; 3] = [
+    Chord::new(Id(5), IdSet::empty().with(Id(0)).with(Id(1))),
+    Chord::new(Id(6), IdSet::empty().with(Id(1)).with(Id(2)).with(Id(3))),
+    // Out of range, and made of a chord
+    Chord::new(Id(40), IdSet::empty().with(Id(5)).with(Id(4))),
+];
+
+const GESTURES: [Gesture<'static>; 4] = [
+    Gesture::new(0, Id(0), &[Length::Short, Length::Short, Length::Long], Ms(300)),
+    Gesture::new(1, Id(0), &[Length::Short, Length::Short], Ms(300)),
+    Gesture::new(2, Id(5), &[Length::Medium], Ms(0)),
+    Gesture::new(3, Id(2), &[Length::Short; 10], Ms(u64::MAX)),
+];
+
+const REPEATED_PRESS_SUPPORT: [Id; 3] = [Id(0), Id(2), Id(50)];
+
+/// Small xorshift generator, for timelines reproducible from their seed.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn chance(&mut self, percent: u64) -> bool {
+        self.below(100) < percent
+    }
+
+    fn pick<T: Copy>(&mut self, values: &[T]) -> T {
+        values[self.below(values.len() as u64) as usize]
+    }
+
+    fn duration(&mut self) -> Ms {
+        Ms(self.pick(&[0, 1, 10, 50, 80, 300, 1000, 5000, u64::MAX / 2, u64::MAX]))
+    }
+
+    fn auto_repeat(&mut self) -> Option<AutoRepeat> {
+        self.chance(50).then(|| AutoRepeat {
+            initial_delay: self.duration(),
+            interval: self.duration(),
+            min_interval: self.duration(),
+            acceleration: self.duration(),
+        })
+    }
+
+    fn overrides(&mut self, id: Id) -> ButtonOverrides {
+        let mut overrides = ButtonOverrides::new(id);
+        if self.chance(50) {
+            overrides.short_press_duration = Some(self.duration());
+            overrides.long_press_duration = Some(self.duration());
+            overrides.hold_event_interval = Some(self.duration());
+            overrides.repeated_press_support = Some(self.chance(50));
+            overrides.repeated_press_mode = Some(self.pick(&[RepeatedPressMode::Immediate, RepeatedPressMode::Deferred]));
+            overrides.enable_raw_press_release_events = Some(self.chance(50));
+        }
+        overrides.auto_repeat = Some(self.auto_repeat());
+        overrides
+    }
+
+    fn config<'a>(&mut self, overrides: &'a [ButtonOverrides]) -> Config<'a> {
+        Config {
+            short_press_duration: self.duration(),
+            medium_press_duration: self.duration(),
+            long_press_duration: self.duration(),
+            very_long_press_duration: self.duration(),
+            hold_event_interval: self.duration(),
+            repeated_press_threshold_duration: self.duration(),
+            buttons_with_repeated_press_support: self.chance(50).then_some(&REPEATED_PRESS_SUPPORT[..]),
+            repeated_press_mode: self.pick(&[RepeatedPressMode::Immediate, RepeatedPressMode::Deferred]),
+            enable_raw_press_release_events: self.chance(50),
+            auto_repeat: self.auto_repeat(),
+            chords: &CHORDS[..self.below(CHORDS.len() as u64 + 1) as usize],
+            chord_window: self.duration(),
+            gestures: &GESTURES[..self.below(GESTURES.len() as u64 + 1) as usize],
+            overrides,
+        }
+    }
+
+    /// Mostly small steps forward, sometimes none, huge ones or jumps anywhere.
+    fn time_step(&mut self, now: Ms) -> Ms {
+        match self.below(100) {
+            0..=9 => now,
+            10..=89 => now + Ms(1 + self.below(60)),
+            90..=95 => now + Ms(self.below(10_000)),
+            96..=97 => now + self.duration(),
+            _ => Ms(self.next()),
+        }
+    }
+}
+
+/// What the events sent for a button so far imply.
+#[derive(Default, Clone, Copy)]
+struct Expected {
+    raw_pressed: bool,
+    press: Option<Kind>,
+    repeats: u16,
+}
+
+impl Expected {
+    fn check(&mut self, event: Event) -> Result<(), &'static str> {
+        match event {
+            Event::Press(Kind::Raw) if self.raw_pressed => return Err("raw press while pressed"),
+            Event::Press(Kind::Raw) => self.raw_pressed = true,
+            Event::Release(Kind::Raw) if !self.raw_pressed => return Err("raw release while released"),
+            Event::Release(Kind::Raw) => self.raw_pressed = false,
+            Event::Press(kind) => {
+                match self.press {
+                    // The same press only changes its length
+                    Some(open) if shortened(open) != shortened(kind) => return Err("press changed its kind"),
+                    Some(_) => {}
+                    None => self.repeats = 0,
+                }
+                self.press = Some(kind);
+            }
+            Event::Release(kind) if self.press != Some(kind) => return Err("release not matching its press"),
+            Event::Release(_) => self.press = None,
+            Event::Repeat(_) if self.press.is_none() => return Err("repeat while released"),
+            Event::Repeat(count) if count != self.repeats.saturating_add(1) => return Err("repeat count skipped"),
+            Event::Repeat(count) => self.repeats = count,
+            Event::Hold(_) if self.press.is_none() => return Err("hold while released"),
+            Event::Hold(_) | Event::Gesture(_) => {}
+        }
+        Ok(())
+    }
+}
+
+/// Gets a press kind with its length left out, to compare the events of the same press.
+fn shortened(kind: Kind) -> Kind {
+    match kind {
+        Kind::Raw => Kind::Raw,
+        Kind::Single(_) => Kind::Single(Length::Short),
+        Kind::Double(_) => Kind::Double(Length::Short),
+        Kind::Triple(_) => Kind::Triple(Length::Short),
+        Kind::Repeated(_, count) => Kind::Repeated(Length::Short, count),
+    }
+}
+
+fn run_timeline(seed: u64) {
+    let mut rng = Rng(seed);
+    let mut overrides = Vec::new();
+    for _ in 0..rng.below(4) {
+        let id = Id(rng.below(BUTTONS as u64) as usize);
+        overrides.push(rng.overrides(id));
+    }
+    let config = rng.config(&overrides);
+
+    let clock = ManualClock::new(Ms(rng.next()));
+    let mut buttons = Buttons::<_, BUTTONS>::new(config, &clock);
+    let mut expected = [Expected::default(); BUTTONS];
+    let mut pressed = IdSet::empty();
+
+    for step in 0..STEPS {
+        // Buttons mostly stay as they are, and ids past the buttons show up too
+        if rng.chance(30) {
+            pressed = IdSet(pressed.0 ^ (1 << rng.below(IdSet::CAPACITY as u64)));
+        }
+
+        for (id, event) in buttons.process_input(pressed).iter() {
+            if let Err(error) = expected[id.0].check(event) {
+                panic!("seed {seed:#x}, step {step}: {error}, got {event:?} for button {}", id.0);
+            }
+        }
+        let _ = buttons.next_deadline();
+
+        clock.set(rng.time_step(clock.now()));
+    }
+}
+
+#[test]
+fn random_timelines_never_panic() {
+    let runs = std::env::var("BUTTONS_FUZZ_RUNS")
+        .ok()
+        .and_then(|runs| runs.parse().ok())
+        .unwrap_or(DEFAULT_RUNS);
+
+    for run in 0..runs {
+        // Xorshift needs a non-zero seed
+        run_timeline(0x9E37_79B9_7F4A_7C15 ^ (run + 1));
+    }
+}
+
+#[test]
+fn zero_durations_send_every_kind_of_event() {
+    let clock = ManualClock::default();
+    let config = Config {
+        short_press_duration: Ms(0),
+        medium_press_duration: Ms(0),
+        long_press_duration: Ms(0),
+        very_long_press_duration: Ms(0),
+        hold_event_interval: Ms(0),
+        repeated_press_threshold_duration: Ms(0),
+        repeated_press_mode: RepeatedPressMode::Deferred,
+        enable_raw_press_release_events: true,
+        auto_repeat: Some(AutoRepeat {
+            initial_delay: Ms(0),
+            interval: Ms(0),
+            min_interval: Ms(0),
+            acceleration: Ms(0),
+        }),
+        ..Config::DEFAULT
+    };
+    let mut buttons = Buttons::<_, 1>::new(config, &clock);
+
+    // Every kind of event in the same updates
+    let mut events = Vec::new();
+    for _ in 0..3 {
+        events.extend(buttons.process_input(Id(0).into()).iter().map(|(_, event)| event));
+        clock.advance(Ms(1));
+    }
+    events.extend(buttons.process_input(IdSet::empty()).iter().map(|(_, event)| event));
+
+    let very_long = Kind::Single(Length::VeryLong);
+    assert_eq!(events.first(), Some(&Event::Press(Kind::Raw)));
+    assert!(events.contains(&Event::Press(very_long)));
+    assert!(events.contains(&Event::Repeat(2)));
+    assert!(events.iter().any(|event| matches!(event, Event::Hold(_))));
+    assert_eq!(events[events.len() - 2..], [Event::Release(Kind::Raw), Event::Release(very_long)]);
+}
+
+#[test]
+fn busiest_update_fits() {
+    const GESTURES: [Gesture<'static>; 2] = [
+        Gesture::new(0, Id(0), &[Length::VeryLong], Ms(10)),
+        Gesture::new(1, Id(0), &[Length::VeryLong, Length::VeryLong], Ms(10)),
+    ];
+
+    let clock = ManualClock::default();
+    let config = Config {
+        short_press_duration: Ms(0),
+        medium_press_duration: Ms(0),
+        long_press_duration: Ms(0),
+        very_long_press_duration: Ms(0),
+        repeated_press_threshold_duration: Ms(0),
+        enable_raw_press_release_events: true,
+        auto_repeat: Some(AutoRepeat {
+            initial_delay: Ms(0),
+            interval: Ms(100),
+            min_interval: Ms(100),
+            acceleration: Ms(0),
+        }),
+        gestures: &GESTURES,
+        ..Config::DEFAULT
+    };
+    let mut buttons = Buttons::<_, 1>::new(config, &clock);
+
+    buttons.process_input(Id(0).into());
+    clock.set(Ms(1));
+    buttons.process_input(IdSet::empty());
+
+    // The first gesture times out as the next press starts, in the same update as its events
+    clock.set(Ms(100));
+    let events: Vec<_> = buttons.process_input(Id(0).into()).iter().map(|(_, event)| event).collect();
+    let very_long = Kind::Single(Length::VeryLong);
+    assert_eq!(
+        events,
+        [Event::Gesture(0), Event::Press(Kind::Raw), Event::Press(very_long), Event::Repeat(1)]
+    );
+}
@@ -0,0 +1,76 @@
+use buttons::{Buttons, Clock, Config, Event, Gesture, Id, IdSet, Length, ManualClock, Ms};
+
+const PLAY: Id = Id(0);
+const BT: Id = Id(1);
+
+const SHORT_SHORT_LONG: u8 = 0;
+const SHORT_SHORT: u8 = 1;
+const SHORT_SHORT_SHORT: u8 = 2;
+
+const GESTURES: [Gesture<'static>; 3] = [
+    Gesture::new(SHORT_SHORT_LONG, PLAY, &[Length::Short, Length::Short, Length::Long], Ms(400)),
+    Gesture::new(SHORT_SHORT, PLAY, &[Length::Short, Length::Short], Ms(400)),
+    Gesture::new(SHORT_SHORT_SHORT, BT, &[Length::Short, Length::Short, Length::Short], Ms(400)),
+];
+
+const CONFIG: Config<'static> = Config {
+    medium_press_duration: Ms(500),
+    long_press_duration: Ms(1000),
+    hold_event_interval: Ms(100_000),
+    repeated_press_threshold_duration: Ms(300),
+    gestures: &GESTURES,
+    ..Config::DEFAULT
+};
+
+/// Presses a button at the given times, as `(press, release)`, polling every 10 ms up to `until`.
+fn play(button: Id, presses: &[(u64, u64)], until: u64) -> Vec<(Ms, Event)> {
+    let clock = ManualClock::default();
+    let mut buttons = Buttons::<_, 2>::new(CONFIG, &clock);
+    let mut gestures = Vec::new();
+
+    while clock.now() < Ms(until) {
+        let now = clock.now().0;
+        let is_pressed = presses.iter().any(|&(press, release)| (press..release).contains(&now));
+        let pressed = if is_pressed { IdSet::from(button) } else { IdSet::empty() };
+
+        for (id, event) in buttons.process_input(pressed).iter() {
+            if let Event::Gesture(_) = event {
+                assert_eq!(id, button);
+                gestures.push((clock.now(), event));
+            }
+        }
+        clock.advance(Ms(10));
+    }
+    gestures
+}
+
+#[test]
+fn complete_gesture_is_sent_on_release() {
+    let gestures = play(PLAY, &[(0, 100), (300, 400), (600, 1800)], 3000);
+    assert_eq!(gestures, [(Ms(1800), Event::Gesture(SHORT_SHORT_LONG))]);
+}
+
+#[test]
+fn gesture_starting_a_longer_one_waits_for_the_gap() {
+    let gestures = play(PLAY, &[(0, 100), (300, 400)], 2000);
+    assert_eq!(gestures, [(Ms(810), Event::Gesture(SHORT_SHORT))]);
+}
+
+#[test]
+fn partial_gestures_time_out() {
+    // The third press comes too late, and starts a new sequence
+    let gestures = play(BT, &[(0, 100), (300, 400), (900, 1000), (1200, 1300), (1500, 1600)], 3000);
+    assert_eq!(gestures, [(Ms(1600), Event::Gesture(SHORT_SHORT_SHORT))]);
+
+    // Presses of the wrong length restart the sequence
+    let gestures = play(BT, &[(0, 100), (300, 1000), (1200, 1300), (1500, 1600), (1800, 1900)], 3000);
+    assert_eq!(gestures, [(Ms(1900), Event::Gesture(SHORT_SHORT_SHORT))]);
+}
+
+#[test]
+fn gap_ends_when_the_button_is_pressed() {
+    // The third press starts 390 ms after the second one is released, its press event only
+    // coming once debounced, past the gap
+    let gestures = play(PLAY, &[(0, 100), (300, 400), (790, 1800)], 3000);
+    assert_eq!(gestures, [(Ms(1800), Event::Gesture(SHORT_SHORT_LONG))]);
+}
@@ -0,0 +1,72 @@
+use buttons::{ButtonOverrides, Buttons, Config, Event, Id, IdSet, Kind, Length, ManualClock, Ms, RepeatedPressMode};
+
+mod common;
+use common::run;
+
+const POWER: Id = Id(0);
+const PLUS: Id = Id(1);
+
+const OVERRIDES: [ButtonOverrides; 2] = [
+    ButtonOverrides {
+        long_press_duration: Some(Ms(2000)),
+        enable_raw_press_release_events: Some(true),
+        ..ButtonOverrides::new(POWER)
+    },
+    ButtonOverrides {
+        hold_event_interval: Some(Ms(400)),
+        repeated_press_support: Some(false),
+        ..ButtonOverrides::new(PLUS)
+    },
+];
+
+const CONFIG: Config<'static> = Config {
+    hold_event_interval: Ms(100_000),
+    repeated_press_mode: RepeatedPressMode::Deferred,
+    overrides: &OVERRIDES,
+    ..Config::DEFAULT
+};
+
+#[test]
+fn overrides_fall_back_to_the_config() {
+    let power = CONFIG.button(POWER);
+    assert_eq!(power.long_press_duration, Ms(2000));
+    assert_eq!(power.medium_press_duration, CONFIG.medium_press_duration);
+    assert!(power.repeated_press_support);
+
+    let plus = CONFIG.button(PLUS);
+    assert_eq!(plus.hold_event_interval, Ms(400));
+    assert!(!plus.repeated_press_support);
+    assert!(!plus.enable_raw_press_release_events);
+
+    // Buttons without overrides get the config as is
+    assert_eq!(CONFIG.button(Id(2)).long_press_duration, CONFIG.long_press_duration);
+}
+
+#[test]
+fn overridden_durations_apply_to_their_button_only() {
+    let clock = ManualClock::default();
+    let mut buttons = Buttons::<_, 2>::new(CONFIG, &clock);
+    let mut events = Vec::new();
+
+    run(&mut buttons, &mut events, IdSet::from(POWER).with(PLUS), 2100);
+    run(&mut buttons, &mut events, IdSet::empty(), 2200);
+
+    let power: Vec<_> = events.iter().filter(|(id, _)| *id == POWER).map(|&(_, event)| event).collect();
+    assert_eq!(
+        power,
+        [
+            Event::Press(Kind::Raw),
+            Event::Press(Kind::Single(Length::Short)),
+            Event::Press(Kind::Single(Length::Medium)),
+            Event::Press(Kind::Single(Length::Long)),
+            Event::Release(Kind::Raw),
+            Event::Release(Kind::Single(Length::Long)),
+        ]
+    );
+
+    // Not deferred, as repeated presses are not detected
+    let plus: Vec<_> = events.iter().filter(|(id, _)| *id == PLUS).map(|&(_, event)| event).collect();
+    assert_eq!(plus[0], Event::Press(Kind::Single(Length::Short)));
+    assert_eq!(plus.iter().filter(|event| matches!(event, Event::Hold(_))).count(), 4);
+    assert_eq!(plus.last(), Some(&Event::Release(Kind::Single(Length::Medium))));
+}
@@ -0,0 +1,412 @@
+//! Golden tests written as textual timelines.
+//!
+//! A timeline is a list of `t=<ms> <command>` steps separated by `;`, the commands being
+//! `press <ids>`, `release <ids>`, `release` for every button, and `end`. The buttons are updated
+//! every millisecond until the end, by default 3 s after the last step. The expected events are
+//! `t=<ms> <id> <event>`, the event written as its `Debug` output, one per line or separated by
+//! `;`.
+
+use buttons::{
+    AutoRepeat, ButtonOverrides, Buttons, Chord, Config, Gesture, Id, IdSet, Length, ManualClock, Ms, RepeatedPressMode,
+};
+
+const CONFIG: Config<'static> = Config {
+    medium_press_duration: Ms(500),
+    long_press_duration: Ms(1000),
+    very_long_press_duration: Ms(2000),
+    hold_event_interval: Ms(100_000),
+    repeated_press_threshold_duration: Ms(300),
+    ..Config::DEFAULT
+};
+
+const DEFERRED: Config<'static> = Config {
+    repeated_press_mode: RepeatedPressMode::Deferred,
+    ..CONFIG
+};
+
+/// Time the buttons keep being updated after the last step, without an `end`.
+const DEFAULT_TAIL: u64 = 3000;
+
+/// A step of a timeline: from `at` on, `pressed` are the buttons pressed.
+struct Step {
+    at: u64,
+    pressed: IdSet,
+}
+
+/// Parses a timeline into its steps and end time.
+fn parse(timeline: &str) -> (Vec<Step>, u64) {
+    let mut steps = Vec::new();
+    let mut pressed = IdSet::empty();
+    let mut end = None;
+
+    for step in timeline.split(';').map(str::trim).filter(|step| !step.is_empty()) {
+        let mut words = step.split_whitespace();
+        let at = words
+            .next()
+            .and_then(|time| time.strip_prefix("t="))
+            .and_then(|time| time.parse().ok())
+            .unwrap_or_else(|| panic!("`{step}` does not start with `t=<ms>`"));
+        let command = words.next().unwrap_or_else(|| panic!("`{step}` has no command"));
+        let ids: IdSet = words
+            .map(|id| Id(id.parse().unwrap_or_else(|_| panic!("`{id}` in `{step}` is not an id"))))
+            .collect();
+
+        match command {
+            "press" => pressed = pressed | ids,
+            "release" if ids.is_empty() => pressed = IdSet::empty(),
+            "release" => pressed = pressed & !ids,
+            "end" => end = Some(at),
+            _ => panic!("unknown command in `{step}`"),
+        }
+        steps.push(Step { at, pressed });
+    }
+
+    let last = steps.last().map_or(0, |step| step.at);
+    (steps, end.unwrap_or(last + DEFAULT_TAIL))
+}
+
+/// Runs a timeline, checking the events it sends.
+#[track_caller]
+fn scenario(config: Config<'_>, timeline: &str, expected: &str) {
+    let (steps, end) = parse(timeline);
+    let clock = ManualClock::default();
+    let mut buttons = Buttons::<_, 8>::new(config, &clock);
+
+    let mut events = Vec::new();
+    for now in 0..=end {
+        clock.set(Ms(now));
+        let step = steps.iter().rev().find(|step| step.at <= now);
+        let pressed = step.map_or(IdSet::empty(), |step| step.pressed);
+
+        for (id, event) in buttons.process_input(pressed).iter() {
+            events.push(format!("t={now} {} {event:?}", id.0));
+        }
+    }
+
+    let expected: Vec<_> = expected
+        .split(['\n', ';'])
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    assert_eq!(events, expected, "timeline `{timeline}`");
+}
+
+#[test]
+fn timelines_are_parsed() {
+    let (steps, end) = parse("t=0 press 1 2; t=60 release 1;t=70 press 3; t=100 release; t=200 end");
+    let pressed: Vec<_> = steps.iter().map(|step| (step.at, step.pressed.0)).collect();
+    assert_eq!(pressed, [(0, 0b0110), (60, 0b0100), (70, 0b1100), (100, 0), (200, 0)]);
+    assert_eq!(end, 200);
+
+    let (_, end) = parse("t=10 press 0");
+    assert_eq!(end, 10 + DEFAULT_TAIL);
+}
+
+#[test]
+fn every_length_of_a_single_press() {
+    scenario(
+        CONFIG,
+        "t=0 press 1; t=2500 release",
+        "
+        t=51 1 Press(Single(Short))
+        t=500 1 Press(Single(Medium))
+        t=1000 1 Press(Single(Long))
+        t=2000 1 Press(Single(VeryLong))
+        t=2500 1 Release(Single(VeryLong))
+        ",
+    );
+}
+
+#[test]
+fn release_at_every_length() {
+    scenario(
+        CONFIG,
+        "t=0 press 1; t=200 release",
+        "t=51 1 Press(Single(Short)); t=200 1 Release(Single(Short))",
+    );
+    scenario(
+        CONFIG,
+        "t=0 press 1; t=700 release",
+        "
+        t=51 1 Press(Single(Short))
+        t=500 1 Press(Single(Medium))
+        t=700 1 Release(Single(Medium))
+        ",
+    );
+    scenario(
+        CONFIG,
+        "t=0 press 1; t=1500 release",
+        "
+        t=51 1 Press(Single(Short))
+        t=500 1 Press(Single(Medium))
+        t=1000 1 Press(Single(Long))
+        t=1500 1 Release(Single(Long))
+        ",
+    );
+}
+
+#[test]
+fn bounces_shorter_than_the_debouncing_are_ignored() {
+    scenario(CONFIG, "t=0 press 1; t=30 release; t=100 press 1; t=130 release", "");
+    scenario(
+        CONFIG,
+        "t=0 press 1; t=30 release; t=100 press 1; t=200 release",
+        "t=151 1 Press(Single(Short)); t=200 1 Release(Single(Short))",
+    );
+
+    // A bounce right after a press is not another press
+    scenario(
+        CONFIG,
+        "t=0 press 1; t=100 release; t=150 press 1; t=160 release",
+        "t=51 1 Press(Single(Short)); t=100 1 Release(Single(Short))",
+    );
+    scenario(
+        DEFERRED,
+        "t=0 press 1; t=100 release; t=150 press 1; t=160 release",
+        "t=451 1 Press(Single(Short)); t=451 1 Release(Single(Short))",
+    );
+}
+
+#[test]
+fn immediate_repeated_presses() {
+    scenario(
+        CONFIG,
+        "t=0 press 1; t=100 release; t=200 press 1; t=300 release; t=400 press 1; t=500 release;
+         t=600 press 1; t=700 release; t=800 press 1; t=1400 release",
+        "
+        t=51 1 Press(Single(Short))
+        t=100 1 Release(Single(Short))
+        t=251 1 Press(Double(Short))
+        t=300 1 Release(Double(Short))
+        t=451 1 Press(Triple(Short))
+        t=500 1 Release(Triple(Short))
+        t=651 1 Press(Repeated(Short, 4))
+        t=700 1 Release(Repeated(Short, 4))
+        t=851 1 Press(Repeated(Short, 5))
+        t=1300 1 Press(Repeated(Medium, 5))
+        t=1400 1 Release(Repeated(Medium, 5))
+        ",
+    );
+}
+
+#[test]
+fn repeated_presses_reset_after_the_threshold() {
+    scenario(
+        CONFIG,
+        "t=0 press 1; t=100 release; t=500 press 1; t=600 release",
+        "
+        t=51 1 Press(Single(Short))
+        t=100 1 Release(Single(Short))
+        t=551 1 Press(Single(Short))
+        t=600 1 Release(Single(Short))
+        ",
+    );
+}
+
+#[test]
+fn deferred_press_waits_for_the_threshold() {
+    // Held past the threshold
+    scenario(
+        DEFERRED,
+        "t=0 press 1; t=700 release",
+        "
+        t=301 1 Press(Single(Short))
+        t=500 1 Press(Single(Medium))
+        t=700 1 Release(Single(Medium))
+        ",
+    );
+
+    // Released before the threshold, the release follows the press right away
+    scenario(
+        DEFERRED,
+        "t=0 press 1; t=100 release",
+        "t=301 1 Press(Single(Short)); t=301 1 Release(Single(Short))",
+    );
+}
+
+#[test]
+fn deferred_repeated_presses_are_sent_once() {
+    scenario(
+        DEFERRED,
+        "t=0 press 1; t=100 release; t=200 press 1; t=300 release; t=400 press 1; t=1200 release",
+        "
+        t=701 1 Press(Triple(Short))
+        t=900 1 Press(Triple(Medium))
+        t=1200 1 Release(Triple(Medium))
+        ",
+    );
+    scenario(
+        DEFERRED,
+        "t=0 press 1; t=100 release; t=200 press 1; t=300 release",
+        "t=501 1 Press(Double(Short)); t=501 1 Release(Double(Short))",
+    );
+}
+
+#[test]
+fn repeated_press_support_per_button() {
+    const ONLY_BUTTON_2: [Id; 1] = [Id(2)];
+    const SCENARIO: Config<'static> = Config {
+        buttons_with_repeated_press_support: Some(&ONLY_BUTTON_2),
+        ..DEFERRED
+    };
+
+    scenario(
+        SCENARIO,
+        "t=0 press 1; t=100 release; t=200 press 1; t=300 release",
+        "
+        t=51 1 Press(Single(Short))
+        t=100 1 Release(Single(Short))
+        t=251 1 Press(Single(Short))
+        t=300 1 Release(Single(Short))
+        ",
+    );
+}
+
+#[test]
+fn raw_events_follow_the_input() {
+    const SCENARIO: Config<'static> = Config {
+        enable_raw_press_release_events: true,
+        ..CONFIG
+    };
+
+    scenario(
+        SCENARIO,
+        "t=0 press 1; t=30 release; t=100 press 1; t=200 release",
+        "
+        t=0 1 Press(Raw)
+        t=30 1 Release(Raw)
+        t=100 1 Press(Raw)
+        t=151 1 Press(Single(Short))
+        t=200 1 Release(Raw)
+        t=200 1 Release(Single(Short))
+        ",
+    );
+}
+
+#[test]
+fn holds_and_repeats_while_pressed() {
+    const SCENARIO: Config<'static> = Config {
+        hold_event_interval: Ms(200),
+        auto_repeat: Some(AutoRepeat {
+            initial_delay: Ms(300),
+            interval: Ms(200),
+            min_interval: Ms(100),
+            acceleration: Ms(100),
+        }),
+        ..CONFIG
+    };
+
+    scenario(
+        SCENARIO,
+        "t=0 press 1; t=650 release",
+        "
+        t=51 1 Press(Single(Short))
+        t=252 1 Hold(Ms(252))
+        t=300 1 Repeat(1)
+        t=453 1 Hold(Ms(453))
+        t=500 1 Press(Single(Medium))
+        t=500 1 Repeat(2)
+        t=600 1 Repeat(3)
+        t=650 1 Release(Single(Medium))
+        ",
+    );
+}
+
+#[test]
+fn combination_changes_mid_press() {
+    // Holding 1 while 2 is tapped, then 1 released while 3 is held
+    scenario(
+        CONFIG,
+        "t=0 press 1; t=200 press 2; t=300 release 2; t=600 press 3; t=700 release 1; t=1200 release",
+        "
+        t=51 1 Press(Single(Short))
+        t=251 2 Press(Single(Short))
+        t=300 2 Release(Single(Short))
+        t=500 1 Press(Single(Medium))
+        t=651 3 Press(Single(Short))
+        t=700 1 Release(Single(Medium))
+        t=1100 3 Press(Single(Medium))
+        t=1200 3 Release(Single(Medium))
+        ",
+    );
+}
+
+#[test]
+fn chords_replace_their_buttons() {
+    const CHORDS: [Chord; 1] = [Chord::new(Id(4), IdSet::empty().with(Id(1)).with(Id(2)))];
+    const SCENARIO: Config<'static> = Config {
+        chords: &CHORDS,
+        chord_window: Ms(100),
+        ..CONFIG
+    };
+
+    scenario(
+        SCENARIO,
+        "t=0 press 1; t=50 press 2; t=700 release 1; t=800 release",
+        "
+        t=101 4 Press(Single(Short))
+        t=550 4 Press(Single(Medium))
+        t=700 4 Release(Single(Medium))
+        ",
+    );
+
+    // Too late for the chord, each button waiting for the window
+    scenario(
+        SCENARIO,
+        "t=0 press 1; t=150 press 2; t=300 release",
+        "
+        t=101 1 Press(Single(Short))
+        t=251 2 Press(Single(Short))
+        t=300 1 Release(Single(Short))
+        t=300 2 Release(Single(Short))
+        ",
+    );
+}
+
+#[test]
+fn gestures_on_top_of_presses() {
+    const GESTURES: [Gesture<'static>; 1] = [Gesture::new(7, Id(1), &[Length::Short, Length::Long], Ms(300))];
+    const SCENARIO: Config<'static> = Config {
+        gestures: &GESTURES,
+        ..CONFIG
+    };
+
+    scenario(
+        SCENARIO,
+        "t=0 press 1; t=100 release; t=300 press 1; t=1500 release",
+        "
+        t=51 1 Press(Single(Short))
+        t=100 1 Release(Single(Short))
+        t=351 1 Press(Double(Short))
+        t=800 1 Press(Double(Medium))
+        t=1300 1 Press(Double(Long))
+        t=1500 1 Release(Double(Long))
+        t=1500 1 Gesture(7)
+        ",
+    );
+}
+
+#[test]
+fn overrides_change_the_timeline_of_their_button() {
+    const OVERRIDES: [ButtonOverrides; 1] = [ButtonOverrides {
+        short_press_duration: Some(Ms(0)),
+        medium_press_duration: Some(Ms(100)),
+        ..ButtonOverrides::new(Id(2))
+    }];
+    const SCENARIO: Config<'static> = Config {
+        overrides: &OVERRIDES,
+        ..CONFIG
+    };
+
+    scenario(
+        SCENARIO,
+        "t=0 press 1 2; t=200 release",
+        "
+        t=0 2 Press(Single(Short))
+        t=51 1 Press(Single(Short))
+        t=100 2 Press(Single(Medium))
+        t=200 1 Release(Single(Short))
+        t=200 2 Release(Single(Medium))
+        ",
+    );
+}
@@ -0,0 +1,55 @@
+use crate::PinState;
+
+/// I2C address of an AW9523B, selected by the levels of its AD0 and AD1 pins.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Address(u8);
+
+impl Address {
+    /// Address with both straps low, the address range being `0x58..=0x5B`.
+    const BASE: u8 = 0x58;
+
+    /// Gets the address selected by the levels of the AD0 and AD1 pins.
+    pub const fn from_straps(ad0: PinState, ad1: PinState) -> Self {
+        let ad0 = matches!(ad0, PinState::High) as u8;
+        let ad1 = matches!(ad1, PinState::High) as u8;
+        Self(Self::BASE | ad1 << 1 | ad0)
+    }
+
+    /// Checks that a 7-bit address can be selected by the straps.
+    pub const fn from_u8(addr: u8) -> Option<Self> {
+        if addr & !0x03 == Self::BASE {
+            Some(Self(addr))
+        } else {
+            None
+        }
+    }
+
+    /// Gets the 7-bit address.
+    pub const fn addr(self) -> u8 {
+        self.0
+    }
+
+    /// Gets the level of the AD0 pin.
+    pub const fn ad0(self) -> PinState {
+        if self.0 & 0x01 != 0 {
+            PinState::High
+        } else {
+            PinState::Low
+        }
+    }
+
+    /// Gets the level of the AD1 pin.
+    pub const fn ad1(self) -> PinState {
+        if self.0 & 0x02 != 0 {
+            PinState::High
+        } else {
+            PinState::Low
+        }
+    }
+}
+
+impl From<Address> for u8 {
+    fn from(address: Address) -> Self {
+        address.addr()
+    }
+}
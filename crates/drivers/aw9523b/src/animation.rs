@@ -0,0 +1,378 @@
+//! LED animations on top of the dimming registers.
+//!
+//! An [`Animator`] runs a fixed number of [`Animation`]s concurrently, each one driving a
+//! [`Target`] made of one or three LED-mode pins. Every call to [`Animator::tick`] renders the
+//! animations into a [`Frame`] holding the level of every dimming register, which is then written
+//! to the device with [`Aw9523b::write_led_frame`] using as few transactions as possible:
+//!
+//! ```ignore
+//! let mut animator = Animator::<2>::new();
+//! animator.start(STATUS_LED, Animation::Breathe { min: 0, max: 255, period: 2000 }, now)?;
+//!
+//! loop {
+//!     io_expander.write_led_frame(animator.tick(now)).await?;
+//! }
+//! ```
+//!
+//! Timestamps are in milliseconds and may wrap around.
+
+use crate::brightness::{Brightness, Curve};
+use crate::{dim_index, next_write_run, Aw9523b, AwError, BasicOps, Pin, Register, DIM_COUNT};
+
+/// Levels of the red, green and blue channels of an RGB LED.
+pub type Color = [u8; 3];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Animation {
+    /// Ramps linearly from `from` to `to` in `duration` ms, then stops at `to`.
+    Fade { from: u8, to: u8, duration: u32 },
+
+    /// Turns on at `level` for `on` ms, then off for `off` ms, repeatedly.
+    Blink { level: u8, on: u32, off: u32 },
+
+    /// Ramps from `min` up to `max` and back down in `period` ms, repeatedly.
+    Breathe { min: u8, max: u8, period: u32 },
+
+    /// Cross-fades through `colors`, taking `transition` ms from one color to the next.
+    ColorCycle { colors: &'static [Color], transition: u32 },
+
+    /// Plays `steps` in order, once or repeatedly.
+    Sequence { steps: &'static [Step], repeat: bool },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+    /// Holds a level for the given number of ms.
+    Hold(u8, u32),
+
+    /// Ramps linearly from the level of the previous step to a level in the given number of ms.
+    Ramp(u8, u32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    /// A single LED.
+    Pin(Pin),
+
+    /// An RGB LED. Monochrome animations are scaled by the color.
+    Rgb([Pin; 3], Color),
+}
+
+/// All the slots of the [`Animator`] are in use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NoFreeSlot;
+
+/// Levels of all the dimming registers, tracking which ones changed since the last write.
+///
+/// Levels are perceptual, the [`Brightness`] of the frame converts them when they are written.
+#[derive(Clone, Debug)]
+pub struct Frame {
+    levels: [u8; DIM_COUNT],
+    dirty: u16,
+    brightness: Brightness,
+}
+
+#[derive(Clone, Copy)]
+struct Slot {
+    target: Target,
+    animation: Animation,
+    start: u32,
+}
+
+/// Runs up to `N` animations concurrently on different pins.
+pub struct Animator<const N: usize> {
+    slots: [Option<Slot>; N],
+    frame: Frame,
+}
+
+impl Animation {
+    /// Samples the animation `elapsed` ms after it started.
+    ///
+    /// Also returns `true` once a one-shot animation finished.
+    fn sample(&self, elapsed: u32) -> (Color, bool) {
+        let (level, finished) = match *self {
+            Animation::Fade { from, to, duration } => {
+                if elapsed >= duration {
+                    (to, true)
+                } else {
+                    (lerp(from, to, elapsed, duration), false)
+                }
+            }
+            Animation::Blink { level, on, off } => {
+                let phase = elapsed % on.saturating_add(off).max(1);
+                (if phase < on { level } else { 0 }, false)
+            }
+            Animation::Breathe { min, max, period } => {
+                let period = period.max(2);
+                let half = period / 2;
+                let phase = elapsed % period;
+                if phase < half {
+                    (lerp(min, max, phase, half), false)
+                } else {
+                    (lerp(max, min, phase - half, period - half), false)
+                }
+            }
+            Animation::ColorCycle { colors, transition } => {
+                return (color_cycle(colors, transition, elapsed), false);
+            }
+            Animation::Sequence { steps, repeat } => sequence(steps, repeat, elapsed),
+        };
+
+        ([level; 3], finished)
+    }
+}
+
+fn lerp(from: u8, to: u8, elapsed: u32, duration: u32) -> u8 {
+    if duration == 0 {
+        return to;
+    }
+
+    let delta = (to as i64 - from as i64) * elapsed.min(duration) as i64 / duration as i64;
+    (from as i64 + delta) as u8
+}
+
+fn color_cycle(colors: &[Color], transition: u32, elapsed: u32) -> Color {
+    if colors.is_empty() {
+        return [0; 3];
+    }
+
+    let transition = transition.max(1);
+    let index = (elapsed / transition) as usize % colors.len();
+    let from = colors[index];
+    let to = colors[(index + 1) % colors.len()];
+    let phase = elapsed % transition;
+
+    [
+        lerp(from[0], to[0], phase, transition),
+        lerp(from[1], to[1], phase, transition),
+        lerp(from[2], to[2], phase, transition),
+    ]
+}
+
+fn sequence(steps: &[Step], repeat: bool, elapsed: u32) -> (u8, bool) {
+    let end_level = |step: &Step| match *step {
+        Step::Hold(level, _) | Step::Ramp(level, _) => level,
+    };
+    let duration = |step: &Step| match *step {
+        Step::Hold(_, duration) | Step::Ramp(_, duration) => duration,
+    };
+
+    let total: u32 = steps.iter().map(duration).fold(0, u32::saturating_add);
+    let Some(last) = steps.last() else {
+        return (0, true);
+    };
+
+    let mut elapsed = elapsed;
+    if elapsed >= total {
+        if !repeat || total == 0 {
+            return (end_level(last), true);
+        }
+        elapsed %= total;
+    }
+
+    // A repeating sequence ramps from where the previous iteration ended
+    let mut previous = if repeat { end_level(last) } else { 0 };
+    for step in steps {
+        if elapsed < duration(step) {
+            let level = match *step {
+                Step::Hold(level, _) => level,
+                Step::Ramp(level, duration) => lerp(previous, level, elapsed, duration),
+            };
+            return (level, false);
+        }
+
+        elapsed -= duration(step);
+        previous = end_level(step);
+    }
+
+    (previous, false)
+}
+
+impl Target {
+    fn pins(&self) -> &[Pin] {
+        match self {
+            Target::Pin(pin) => core::slice::from_ref(pin),
+            Target::Rgb(pins, _) => pins,
+        }
+    }
+
+    fn contains(&self, pin: Pin) -> bool {
+        self.pins().contains(&pin)
+    }
+
+    fn render(&self, frame: &mut Frame, sample: Color) {
+        match *self {
+            Target::Pin(pin) => frame.set(pin, sample[0]),
+            Target::Rgb(pins, color) => {
+                for ((pin, level), scale) in pins.into_iter().zip(sample).zip(color) {
+                    frame.set(pin, (level as u16 * scale as u16 / 255) as u8);
+                }
+            }
+        }
+    }
+}
+
+impl Frame {
+    /// Creates a frame with all the levels off, matching the reset state of the device.
+    pub const fn new() -> Self {
+        Self::with_brightness(Brightness::new(Curve::Linear))
+    }
+
+    /// Creates a frame with all the levels off, corrected by the given brightness when written.
+    pub const fn with_brightness(brightness: Brightness) -> Self {
+        Self {
+            levels: [0; DIM_COUNT],
+            dirty: 0,
+            brightness,
+        }
+    }
+
+    pub fn brightness(&self) -> &Brightness {
+        &self.brightness
+    }
+
+    /// Changes the brightness correction, marking every level for the next write.
+    pub fn set_brightness(&mut self, brightness: Brightness) {
+        self.brightness = brightness;
+        self.invalidate();
+    }
+
+    /// Sets the level of a pin, marking it for the next write if it changed.
+    pub fn set(&mut self, pin: Pin, level: u8) {
+        let index = dim_index(pin);
+        if self.levels[index] != level {
+            self.levels[index] = level;
+            self.dirty |= 1 << index;
+        }
+    }
+
+    /// Gets the level of a pin.
+    pub fn level(&self, pin: Pin) -> u8 {
+        self.levels[dim_index(pin)]
+    }
+
+    /// Checks if some levels changed since the last write.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty != 0
+    }
+
+    /// Marks every level for the next write, e.g. after the device was reset.
+    pub fn invalidate(&mut self) {
+        self.dirty = u16::MAX;
+    }
+
+    /// Gets the next range of dimming registers to write, starting the search at `from`.
+    fn next_run(&self, from: usize) -> Option<(usize, usize)> {
+        next_write_run(from, DIM_COUNT, |i| self.dirty & (1 << i) != 0)
+    }
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Animator<N> {
+    pub const fn new() -> Self {
+        Self {
+            slots: [None; N],
+            frame: Frame::new(),
+        }
+    }
+
+    /// Starts an animation, replacing the animations running on any of the pins of the target.
+    pub fn start(&mut self, target: Target, animation: Animation, now: u32) -> Result<(), NoFreeSlot> {
+        for &pin in target.pins() {
+            self.stop(pin);
+        }
+
+        let slot = self.slots.iter_mut().find(|s| s.is_none()).ok_or(NoFreeSlot)?;
+        *slot = Some(Slot {
+            target,
+            animation,
+            start: now,
+        });
+        Ok(())
+    }
+
+    /// Stops the animation running on a pin, if any. The pins it drove keep their current level.
+    pub fn stop(&mut self, pin: Pin) {
+        for slot in self.slots.iter_mut() {
+            if slot.is_some_and(|s| s.target.contains(pin)) {
+                *slot = None;
+            }
+        }
+    }
+
+    /// Stops the animation running on a pin, if any, and sets the pin to a fixed level.
+    pub fn set_level(&mut self, pin: Pin, level: u8) {
+        self.stop(pin);
+        self.frame.set(pin, level);
+    }
+
+    /// Checks if an animation is running on a pin.
+    pub fn is_animating(&self, pin: Pin) -> bool {
+        self.slots.iter().flatten().any(|s| s.target.contains(pin))
+    }
+
+    /// Advances all the animations to `now` and returns the resulting frame.
+    ///
+    /// One-shot animations are removed once finished, leaving their pins at the final level.
+    pub fn tick(&mut self, now: u32) -> &mut Frame {
+        for slot in self.slots.iter_mut() {
+            let Some(s) = slot else {
+                continue;
+            };
+
+            let (sample, finished) = s.animation.sample(now.wrapping_sub(s.start));
+            s.target.render(&mut self.frame, sample);
+
+            if finished {
+                *slot = None;
+            }
+        }
+
+        &mut self.frame
+    }
+
+    /// Gets the current frame without advancing the animations.
+    pub fn frame(&mut self) -> &mut Frame {
+        &mut self.frame
+    }
+}
+
+impl<const N: usize> Default for Animator<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I2C, RST, E> Aw9523b<I2C, RST>
+where
+    I2C: embedded_hal_async::i2c::I2c + embedded_hal::i2c::ErrorType<Error = E>,
+{
+    /// Writes the levels of a frame that changed since the last write, corrected by its brightness.
+    ///
+    /// Neighbouring dimming registers are written in a single auto-incremented transaction.
+    pub async fn write_led_frame(&mut self, frame: &mut Frame) -> Result<(), AwError<E>> {
+        let mut values = [0u8; DIM_COUNT];
+        let mut from = 0;
+        while let Some((start, end)) = frame.next_run(from) {
+            for (i, value) in (start..end).zip(values.iter_mut()) {
+                *value = frame.brightness.apply_to_dim(i, frame.levels[i]);
+            }
+
+            let register = Register::from_addr(Register::Dim0.addr() + start as u8).unwrap();
+            self.write_registers(register, &values[..end - start]).await?;
+
+            // Only forget about the levels once they were written successfully
+            for i in start..end {
+                frame.dirty &= !(1 << i);
+            }
+            from = end;
+        }
+
+        Ok(())
+    }
+}
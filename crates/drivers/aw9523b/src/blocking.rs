@@ -0,0 +1,81 @@
+//! Blocking access to the AW9523B.
+//!
+//! [`Blocking`] adapts a blocking [`embedded_hal::i2c::I2c`] bus to the async interface used by
+//! the driver. Since the adapter never yields, every driver future completes on its first poll
+//! and can be driven with [`block_on`] outside of an executor, e.g. before it is started:
+//!
+//! ```ignore
+//! let mut io_expander = Aw9523b::new_blocking(i2c, 0x5B);
+//! aw9523b::blocking::block_on(io_expander.set_pin_config(ERROR_LED, PinMode::Output))?;
+//! ```
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+use embedded_hal::i2c::{ErrorType, Operation};
+
+use crate::Aw9523b;
+
+/// Wrapper exposing a blocking I2C bus through the async I2C traits.
+pub struct Blocking<I2C>(pub I2C);
+
+impl<I2C> ErrorType for Blocking<I2C>
+where
+    I2C: ErrorType,
+{
+    type Error = I2C::Error;
+}
+
+impl<I2C> embedded_hal_async::i2c::I2c for Blocking<I2C>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    async fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.0.read(address, read)
+    }
+
+    async fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        self.0.write(address, write)
+    }
+
+    async fn write_read(&mut self, address: u8, write: &[u8], read: &mut [u8]) -> Result<(), Self::Error> {
+        self.0.write_read(address, write, read)
+    }
+
+    async fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), Self::Error> {
+        self.0.transaction(address, operations)
+    }
+}
+
+impl<I2C> Aw9523b<Blocking<I2C>>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Creates a new instance of an AW9523B driver on a blocking I2C bus.
+    pub fn new_blocking(i2c: I2C, addr: impl Into<u8>) -> Self {
+        Self::new(Blocking(i2c), addr)
+    }
+}
+
+/// Runs a future to completion by busy polling it.
+///
+/// Meant for driver futures on a [`Blocking`] bus, which complete on the first poll.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = noop_waker();
+    let mut context = Context::from_waker(&waker);
+
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+fn noop_waker() -> Waker {
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(|_| RAW_WAKER, |_| {}, |_| {}, |_| {});
+    const RAW_WAKER: RawWaker = RawWaker::new(core::ptr::null(), &VTABLE);
+
+    // SAFETY: the vtable functions do nothing and never dereference the data pointer
+    unsafe { Waker::from_raw(RAW_WAKER) }
+}
@@ -0,0 +1,157 @@
+//! Perceptual brightness correction for LEDs driven by the dimming registers.
+//!
+//! The dimming registers set a current, which the eye does not perceive linearly: a linear ramp
+//! looks mostly bright and mixed colors look off. A [`Brightness`] maps perceptual levels to
+//! register values in three stages:
+//!
+//! 1. the global brightness level scales the perceptual level,
+//! 2. the [`Curve`] converts it to a linear current,
+//! 3. the white balance factor of the pin compensates for the efficiency of the LED.
+
+use crate::{dim_index, Pin, DIM_COUNT};
+
+/// Conversion from perceptual levels to linear currents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve {
+    /// No correction, levels are written as they are.
+    Linear,
+
+    /// Power law with an exponent of 2.2.
+    Gamma22,
+
+    /// CIE 1931 lightness, the most even choice for fades.
+    Cie1931,
+
+    /// User provided lookup table.
+    Custom(&'static [u8; 256]),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Brightness {
+    curve: Curve,
+    level: u8,
+    balance: [u8; DIM_COUNT],
+}
+
+static GAMMA22: [u8; 256] = [
+      0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   1,
+      1,   1,   1,   1,   1,   1,   1,   1,   1,   2,   2,   2,   2,   2,   2,   2,
+      3,   3,   3,   3,   3,   4,   4,   4,   4,   5,   5,   5,   5,   6,   6,   6,
+      6,   7,   7,   7,   8,   8,   8,   9,   9,   9,  10,  10,  11,  11,  11,  12,
+     12,  13,  13,  13,  14,  14,  15,  15,  16,  16,  17,  17,  18,  18,  19,  19,
+     20,  20,  21,  22,  22,  23,  23,  24,  25,  25,  26,  26,  27,  28,  28,  29,
+     30,  30,  31,  32,  33,  33,  34,  35,  35,  36,  37,  38,  39,  39,  40,  41,
+     42,  43,  43,  44,  45,  46,  47,  48,  49,  49,  50,  51,  52,  53,  54,  55,
+     56,  57,  58,  59,  60,  61,  62,  63,  64,  65,  66,  67,  68,  69,  70,  71,
+     73,  74,  75,  76,  77,  78,  79,  81,  82,  83,  84,  85,  87,  88,  89,  90,
+     91,  93,  94,  95,  97,  98,  99, 100, 102, 103, 105, 106, 107, 109, 110, 111,
+    113, 114, 116, 117, 119, 120, 121, 123, 124, 126, 127, 129, 130, 132, 133, 135,
+    137, 138, 140, 141, 143, 145, 146, 148, 149, 151, 153, 154, 156, 158, 159, 161,
+    163, 165, 166, 168, 170, 172, 173, 175, 177, 179, 181, 182, 184, 186, 188, 190,
+    192, 194, 196, 197, 199, 201, 203, 205, 207, 209, 211, 213, 215, 217, 219, 221,
+    223, 225, 227, 229, 231, 234, 236, 238, 240, 242, 244, 246, 248, 251, 253, 255,
+];
+
+static CIE1931: [u8; 256] = cie1931_table();
+
+const fn cie1931_table() -> [u8; 256] {
+    let mut table = [0u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        // L* = 100 * i / 255, scaled by 255 to stay in integers
+        let lightness = 100 * i as u64;
+        table[i] = if lightness <= 8 * 255 {
+            // Y = L* / 903.3
+            ((lightness * 10 + 4516) / 9033) as u8
+        } else {
+            // Y = ((L* + 16) / 116)^3
+            let n = lightness + 16 * 255;
+            let den = 116 * 116 * 116 * 255 * 255;
+            ((n * n * n + den / 2) / den) as u8
+        };
+        i += 1;
+    }
+    table
+}
+
+impl Curve {
+    /// Converts a perceptual level into a linear current.
+    pub fn apply(&self, level: u8) -> u8 {
+        match self {
+            Curve::Linear => level,
+            Curve::Gamma22 => GAMMA22[level as usize],
+            Curve::Cie1931 => CIE1931[level as usize],
+            Curve::Custom(table) => table[level as usize],
+        }
+    }
+}
+
+impl Brightness {
+    /// Creates a brightness correction at full level with no white balance adjustment.
+    pub const fn new(curve: Curve) -> Self {
+        Self {
+            curve,
+            level: u8::MAX,
+            balance: [u8::MAX; DIM_COUNT],
+        }
+    }
+
+    /// Sets the global brightness level, `255` being full brightness.
+    pub const fn with_level(mut self, level: u8) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Sets the white balance factor of a pin, `255` leaving its current untouched.
+    pub const fn with_balance(mut self, pin: Pin, factor: u8) -> Self {
+        self.balance[dim_index(pin)] = factor;
+        self
+    }
+
+    pub fn curve(&self) -> Curve {
+        self.curve
+    }
+
+    pub fn set_curve(&mut self, curve: Curve) {
+        self.curve = curve;
+    }
+
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    pub fn set_level(&mut self, level: u8) {
+        self.level = level;
+    }
+
+    pub fn balance(&self, pin: Pin) -> u8 {
+        self.balance[dim_index(pin)]
+    }
+
+    pub fn set_balance(&mut self, pin: Pin, factor: u8) {
+        self.balance[dim_index(pin)] = factor;
+    }
+
+    /// Converts the perceptual level of a pin into the value of its dimming register.
+    pub fn apply(&self, pin: Pin, level: u8) -> u8 {
+        self.apply_to_dim(dim_index(pin), level)
+    }
+
+    /// Converts a perceptual level into the value of the dimming register at `DIM0 + index`.
+    pub(crate) fn apply_to_dim(&self, index: usize, level: u8) -> u8 {
+        let level = scale(level, self.level);
+        let current = self.curve.apply(level);
+        scale(current, self.balance[index])
+    }
+}
+
+impl Default for Brightness {
+    fn default() -> Self {
+        Self::new(Curve::Linear)
+    }
+}
+
+/// Scales a value by `factor / 255`, rounding to the nearest integer.
+fn scale(value: u8, factor: u8) -> u8 {
+    ((value as u16 * factor as u16 + 127) / 255) as u8
+}
@@ -0,0 +1,299 @@
+//! Declarative configuration of the whole device.
+//!
+//! An [`Aw9523bConfig`] describes the mode of every pin along with the settings shared by all of
+//! them, and can be built in a `const`:
+//!
+//! ```ignore
+//! const CONFIG: Aw9523bConfig = Aw9523bConfig::new()
+//!     .with_pin_mode(BUTTON, PinMode::Input)
+//!     .with_interrupt(BUTTON, true)
+//!     .with_pin_mode(LED, PinMode::Led)
+//!     .with_drive_current(DriveCurrent::Mid);
+//!
+//! io_expander.configure(&CONFIG).await?;
+//! ```
+//!
+//! The driver only writes the registers that may not hold their configured value yet, and
+//! [`Aw9523b::verify_config`] reads the configuration back to compare it.
+
+use crate::recovery::{READABLE_RANGES, SHADOW_SIZE};
+use crate::register::{ConfigPort, IntPort, LedModeSwitch, OutputPort, TypedRegister};
+use crate::{dim_index, next_write_run, Aw9523b, AwError, BasicOps, DriveCurrent, Pin, PinMode, PinState, Port, Port0OutputDriveMode, Register, DIM_COUNT};
+
+/// Registers holding configuration, as `(first, count)` ranges in the order they are written.
+const CONFIG_RANGES: [(Register, usize); 3] = [(Register::OutputPort0, 6), (Register::Dim0, DIM_COUNT), (Register::Ctl, 3)];
+
+/// Configuration of every pin of the device, along with the settings shared by all of them.
+///
+/// Holds the raw content of the configuration registers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Aw9523bConfig {
+    outputs: [u8; 2],
+    directions: [u8; 2],
+    interrupts: [u8; 2],
+    ctl: u8,
+    led_mode_switch: [u8; 2],
+    dim: [u8; DIM_COUNT],
+}
+
+impl Aw9523bConfig {
+    /// Creates a configuration matching the reset state of the device, with all the outputs low.
+    ///
+    /// Every pin is a GPIO output with its interrupt enabled, port 0 is open-drain and the LED
+    /// drive current is the maximum.
+    pub const fn new() -> Self {
+        Self {
+            outputs: [0x00; 2],
+            directions: [0x00; 2],
+            interrupts: [0x00; 2],
+            ctl: 0x00,
+            led_mode_switch: [0xFF; 2],
+            dim: [0x00; DIM_COUNT],
+        }
+    }
+
+    pub const fn with_pin_mode(mut self, pin: Pin, mode: PinMode) -> Self {
+        // Config: 1 = input. LED mode switch: 0 = LED, 1 = GPIO
+        let (input, gpio) = match mode {
+            PinMode::Input => (true, true),
+            PinMode::Output => (false, true),
+            PinMode::Led => (false, false),
+        };
+
+        let port = pin.0 as usize;
+        self.directions[port] = with_bit(self.directions[port], pin, input);
+        self.led_mode_switch[port] = with_bit(self.led_mode_switch[port], pin, gpio);
+        self
+    }
+
+    /// Enables or disables the interrupt of an input pin.
+    pub const fn with_interrupt(mut self, pin: Pin, enabled: bool) -> Self {
+        // Interrupt: 0 = enabled
+        let port = pin.0 as usize;
+        self.interrupts[port] = with_bit(self.interrupts[port], pin, !enabled);
+        self
+    }
+
+    /// Sets the initial state of a GPIO output pin.
+    pub const fn with_output(mut self, pin: Pin, state: PinState) -> Self {
+        let port = pin.0 as usize;
+        self.outputs[port] = with_bit(self.outputs[port], pin, matches!(state, PinState::High));
+        self
+    }
+
+    pub const fn with_port0_drive_mode(mut self, mode: Port0OutputDriveMode) -> Self {
+        // CTL.GPOMD: 1 = push-pull
+        let push_pull = matches!(mode, Port0OutputDriveMode::PushPull);
+        self.ctl = (self.ctl & !0x10) | if push_pull { 0x10 } else { 0x00 };
+        self
+    }
+
+    pub const fn with_drive_current(mut self, current: DriveCurrent) -> Self {
+        self.ctl = (self.ctl & !0x03) | current as u8;
+        self
+    }
+
+    /// Sets the initial dimming level of an LED-mode pin.
+    pub const fn with_dim_level(mut self, pin: Pin, level: u8) -> Self {
+        self.dim[dim_index(pin)] = level;
+        self
+    }
+
+    pub const fn pin_mode(&self, pin: Pin) -> PinMode {
+        let port = pin.0 as usize;
+        match (bit(self.directions[port], pin), bit(self.led_mode_switch[port], pin)) {
+            (true, _) => PinMode::Input,
+            (false, true) => PinMode::Output,
+            (false, false) => PinMode::Led,
+        }
+    }
+
+    /// Checks if the interrupt of a pin is enabled.
+    pub const fn interrupt(&self, pin: Pin) -> bool {
+        !bit(self.interrupts[pin.0 as usize], pin)
+    }
+
+    pub const fn output(&self, pin: Pin) -> PinState {
+        if bit(self.outputs[pin.0 as usize], pin) {
+            PinState::High
+        } else {
+            PinState::Low
+        }
+    }
+
+    pub const fn port0_drive_mode(&self) -> Port0OutputDriveMode {
+        if self.ctl & 0x10 != 0 {
+            Port0OutputDriveMode::PushPull
+        } else {
+            Port0OutputDriveMode::OpenDrain
+        }
+    }
+
+    pub const fn drive_current(&self) -> DriveCurrent {
+        match self.ctl & 0x03 {
+            0 => DriveCurrent::Max,
+            1 => DriveCurrent::High,
+            2 => DriveCurrent::Mid,
+            _ => DriveCurrent::Low,
+        }
+    }
+
+    pub const fn dim_level(&self, pin: Pin) -> u8 {
+        self.dim[dim_index(pin)]
+    }
+
+    /// Compares the readable registers of two configurations.
+    ///
+    /// Dimming levels are not compared, the dimming registers being write-only.
+    pub fn diff(&self, actual: &Aw9523bConfig) -> ConfigDiff {
+        ConfigDiff {
+            expected: self.registers(),
+            actual: actual.registers(),
+        }
+    }
+
+    /// Raw content of the configuration registers, indexed by address.
+    fn registers(&self) -> [u8; SHADOW_SIZE] {
+        let mut registers = [0x00; SHADOW_SIZE];
+        for port in [Port::Port0, Port::Port1] {
+            let i = port as usize;
+            registers[OutputPort::register(port).addr() as usize] = self.outputs[i];
+            registers[ConfigPort::register(port).addr() as usize] = self.directions[i];
+            registers[IntPort::register(port).addr() as usize] = self.interrupts[i];
+            registers[LedModeSwitch::register(port).addr() as usize] = self.led_mode_switch[i];
+        }
+        registers[Register::Ctl.addr() as usize] = self.ctl;
+
+        let dim = Register::Dim0.addr() as usize;
+        registers[dim..dim + DIM_COUNT].copy_from_slice(&self.dim);
+        registers
+    }
+
+    fn from_registers(registers: &[u8; SHADOW_SIZE]) -> Self {
+        let mut config = Self::new();
+        for port in [Port::Port0, Port::Port1] {
+            let i = port as usize;
+            config.outputs[i] = registers[OutputPort::register(port).addr() as usize];
+            config.directions[i] = registers[ConfigPort::register(port).addr() as usize];
+            config.interrupts[i] = registers[IntPort::register(port).addr() as usize];
+            config.led_mode_switch[i] = registers[LedModeSwitch::register(port).addr() as usize];
+        }
+        config.ctl = registers[Register::Ctl.addr() as usize];
+
+        let dim = Register::Dim0.addr() as usize;
+        config.dim.copy_from_slice(&registers[dim..dim + DIM_COUNT]);
+        config
+    }
+}
+
+/// Readable registers differing between two configurations, see [`Aw9523bConfig::diff`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConfigDiff {
+    expected: [u8; SHADOW_SIZE],
+    actual: [u8; SHADOW_SIZE],
+}
+
+/// A register differing between two configurations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RegisterDiff {
+    pub register: Register,
+    pub expected: u8,
+    pub actual: u8,
+}
+
+impl ConfigDiff {
+    /// Checks if both configurations match.
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Iterates over the differing registers, in address order.
+    pub fn iter(&self) -> impl Iterator<Item = RegisterDiff> + '_ {
+        READABLE_RANGES
+            .into_iter()
+            .flat_map(|(first, count)| first.addr() as usize..first.addr() as usize + count)
+            .filter(|&addr| self.expected[addr] != self.actual[addr])
+            .map(|addr| RegisterDiff {
+                register: Register::from_addr(addr as u8).unwrap(),
+                expected: self.expected[addr],
+                actual: self.actual[addr],
+            })
+    }
+}
+
+impl Default for Aw9523bConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const fn bit(value: u8, pin: Pin) -> bool {
+    value & (1 << pin.1 as u8) != 0
+}
+
+const fn with_bit(value: u8, pin: Pin, set: bool) -> u8 {
+    let mask = 1 << pin.1 as u8;
+    if set {
+        value | mask
+    } else {
+        value & !mask
+    }
+}
+
+impl<I2C, RST, E> Aw9523b<I2C, RST>
+where
+    I2C: embedded_hal_async::i2c::I2c + embedded_hal::i2c::ErrorType<Error = E>,
+{
+    /// Writes a configuration to the device.
+    ///
+    /// Only the registers that may differ from the configuration are written, which after a
+    /// reset of the device are the ones differing from their default value. Neighbouring
+    /// registers are written in a single auto-incremented transaction.
+    ///
+    /// The outputs are written before the directions and the dimming levels before the LED mode
+    /// switches, so that pins start at their configured state.
+    pub async fn configure(&mut self, config: &Aw9523bConfig) -> Result<(), AwError<E>> {
+        let registers = config.registers();
+        for (first, count) in CONFIG_RANGES {
+            let first = first.addr() as usize;
+            let mut from = first;
+            while let Some((start, end)) = self.next_stale_run(&registers, from, first + count) {
+                let register = Register::from_addr(start as u8).unwrap();
+                self.write_registers(register, &registers[start..end]).await?;
+                from = end;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the configuration of the device.
+    ///
+    /// The dimming registers being write-only, the levels are the last ones written through the
+    /// driver, or 0 when unknown.
+    pub async fn read_config(&mut self) -> Result<Aw9523bConfig, AwError<E>> {
+        let mut registers = [0x00; SHADOW_SIZE];
+        for (first, count) in READABLE_RANGES {
+            let start = first.addr() as usize;
+            self.read_registers(first, &mut registers[start..start + count]).await?;
+        }
+
+        let dim = Register::Dim0.addr() as usize;
+        for (addr, level) in registers.iter_mut().enumerate().skip(dim).take(DIM_COUNT) {
+            *level = self.shadow.known_value(addr).unwrap_or(0x00);
+        }
+
+        Ok(Aw9523bConfig::from_registers(&registers))
+    }
+
+    /// Compares the configuration of the device against the expected one.
+    pub async fn verify_config(&mut self, config: &Aw9523bConfig) -> Result<ConfigDiff, AwError<E>> {
+        let actual = self.read_config().await?;
+        Ok(config.diff(&actual))
+    }
+
+    /// Gets the next range of registers within `from..end` that are not known to hold their
+    /// configured value.
+    fn next_stale_run(&self, registers: &[u8; SHADOW_SIZE], from: usize, end: usize) -> Option<(usize, usize)> {
+        next_write_run(from, end, |addr| self.shadow.known_value(addr) != Some(registers[addr]))
+    }
+}
@@ -0,0 +1,219 @@
+//! Snapshot of the whole state of the device, for debugging.
+//!
+//! A [`Snapshot`] holds the value of every register at one point in time. It is printed decoded,
+//! pin by pin, with `defmt`, and two snapshots can be compared to see what some code changed:
+//!
+//! ```ignore
+//! let before = io_expander.snapshot().await?;
+//! ui.set_status_led(0xFF, 0, 0).await?;
+//! let after = io_expander.snapshot().await?;
+//! defmt::info!("{}\n{}", after, before.diff(&after));
+//! ```
+//!
+//! The dimming registers are write-only: their values come from what the driver wrote, and are
+//! unknown for registers not written since the device was last reset.
+
+use crate::group::GroupPin;
+use crate::recovery::{Shadow, SHADOW_SIZE};
+use crate::register::{ConfigPort, Ctl, Direction, IntPort, Interrupt, LedMode, LedModeSwitch, TypedRegister};
+use crate::{
+    dim_register, Aw9523b, AwError, BasicOps, DriveCurrent, Pin, PinMode, PinState, Port, Port0OutputDriveMode,
+    Register,
+};
+
+/// Registers read back from the device, as `(first, count)` ranges.
+const READ_RANGES: [(Register, usize); 2] = [(Register::InputPort0, 8), (Register::Id, 4)];
+
+/// Every pin of the device, port 0 first.
+fn pins() -> impl Iterator<Item = Pin> {
+    (0..16).map(|index| GroupPin::from_index(index).pin)
+}
+
+/// State of every register of the device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Register values by address, `None` when the register cannot be read and was not written.
+    registers: [Option<u8>; SHADOW_SIZE],
+}
+
+impl Snapshot {
+    fn new(shadow: &Shadow) -> Self {
+        let mut registers = [None; SHADOW_SIZE];
+        for pin in pins() {
+            let addr = dim_register(pin).addr() as usize;
+            registers[addr] = shadow.known_value(addr);
+        }
+        Self { registers }
+    }
+
+    /// Gets the value of a register, if known.
+    pub fn register(&self, register: Register) -> Option<u8> {
+        self.registers.get(register.addr() as usize).copied().flatten()
+    }
+
+    fn known(&self, register: Register) -> u8 {
+        // Every register but the dimming ones is read back
+        self.register(register).unwrap_or_default()
+    }
+
+    pub fn device_id(&self) -> u8 {
+        self.known(Register::Id)
+    }
+
+    pub fn pin_mode(&self, pin: Pin) -> PinMode {
+        let config = ConfigPort::from_raw(self.known(ConfigPort::register(pin.0)));
+        let led_mode_switch = LedModeSwitch::from_raw(self.known(LedModeSwitch::register(pin.0)));
+
+        match (config.get(pin.1), led_mode_switch.get(pin.1)) {
+            (Direction::Input, _) => PinMode::Input,
+            (Direction::Output, LedMode::Gpio) => PinMode::Output,
+            (Direction::Output, LedMode::Led) => PinMode::Led,
+        }
+    }
+
+    /// Gets the level seen on a pin.
+    pub fn input(&self, pin: Pin) -> PinState {
+        let register = match pin.0 {
+            Port::Port0 => Register::InputPort0,
+            Port::Port1 => Register::InputPort1,
+        };
+        PinState::from(self.known(register) & (1 << pin.1 as u8) != 0)
+    }
+
+    /// Gets the level a pin drives in output mode.
+    pub fn output(&self, pin: Pin) -> PinState {
+        let register = match pin.0 {
+            Port::Port0 => Register::OutputPort0,
+            Port::Port1 => Register::OutputPort1,
+        };
+        PinState::from(self.known(register) & (1 << pin.1 as u8) != 0)
+    }
+
+    pub fn interrupt(&self, pin: Pin) -> bool {
+        let interrupts = IntPort::from_raw(self.known(IntPort::register(pin.0)));
+        interrupts.get(pin.1) == Interrupt::Enabled
+    }
+
+    /// Gets the dimming level of a pin, if known.
+    pub fn dim_level(&self, pin: Pin) -> Option<u8> {
+        self.register(dim_register(pin))
+    }
+
+    pub fn port0_drive_mode(&self) -> Port0OutputDriveMode {
+        Ctl::from_raw(self.known(Register::Ctl)).port0_drive_mode()
+    }
+
+    pub fn drive_current(&self) -> DriveCurrent {
+        Ctl::from_raw(self.known(Register::Ctl)).drive_current()
+    }
+
+    /// Gets the registers that changed from this snapshot to a later one.
+    pub fn diff(&self, after: &Snapshot) -> SnapshotDiff {
+        SnapshotDiff {
+            before: *self,
+            after: *after,
+        }
+    }
+}
+
+/// Registers differing between two snapshots, see [`Snapshot::diff`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    before: Snapshot,
+    after: Snapshot,
+}
+
+/// A register that changed between two snapshots.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RegisterChange {
+    pub register: Register,
+    pub before: Option<u8>,
+    pub after: Option<u8>,
+}
+
+impl SnapshotDiff {
+    /// Checks if both snapshots match.
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Iterates over the changed registers, in address order.
+    pub fn iter(&self) -> impl Iterator<Item = RegisterChange> + '_ {
+        (0..SHADOW_SIZE as u8)
+            .filter_map(Register::from_addr)
+            .map(|register| RegisterChange {
+                register,
+                before: self.before.register(register),
+                after: self.after.register(register),
+            })
+            .filter(|change| change.before != change.after)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Snapshot {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "AW9523B id={=u8:#x} port0={} current={}",
+            self.device_id(),
+            self.port0_drive_mode(),
+            self.drive_current()
+        );
+
+        for pin in pins() {
+            defmt::write!(
+                f,
+                "\n  P{=u8}.{=u8}: {} in={} out={} int={=bool} dim={}",
+                pin.0 as u8,
+                pin.1 as u8,
+                self.pin_mode(pin),
+                self.input(pin),
+                self.output(pin),
+                self.interrupt(pin),
+                self.dim_level(pin)
+            );
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for SnapshotDiff {
+    fn format(&self, f: defmt::Formatter) {
+        if self.is_empty() {
+            defmt::write!(f, "no register changed");
+        }
+
+        for (i, change) in self.iter().enumerate() {
+            if i > 0 {
+                defmt::write!(f, "\n");
+            }
+            defmt::write!(f, "{}: {} -> {}", change.register, change.before, change.after);
+        }
+    }
+}
+
+impl<I2C, RST, E> Aw9523b<I2C, RST>
+where
+    I2C: embedded_hal_async::i2c::I2c + embedded_hal::i2c::ErrorType<Error = E>,
+{
+    /// Reads every readable register, in two transactions.
+    ///
+    /// Reading the input ports clears a pending interrupt.
+    pub async fn snapshot(&mut self) -> Result<Snapshot, AwError<E>> {
+        let mut snapshot = Snapshot::new(&self.shadow);
+
+        for (first, count) in READ_RANGES {
+            let start = first.addr() as usize;
+            let mut values = [0u8; 8];
+            self.read_registers(first, &mut values[..count]).await?;
+
+            for (addr, value) in (start..start + count).zip(values) {
+                snapshot.registers[addr] = Some(value);
+            }
+        }
+
+        Ok(snapshot)
+    }
+}
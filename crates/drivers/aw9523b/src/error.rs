@@ -1,8 +1,56 @@
+//! Errors returned by the driver.
+
+use crate::Register;
+
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Error<I2cError> {
-    /// I2C bus error.
-    I2c(I2cError),
+    /// I2C bus error, once the retries allowed by the [`RetryPolicy`](crate::RetryPolicy) are
+    /// exhausted.
+    I2c { error: I2cError, context: Context },
 
     /// Attempted to write to a read-only register.
-    WriteToReadOnly,
+    WriteToReadOnly(Register),
+
+    /// The device at the address is not an AW9523B.
+    UnexpectedDeviceId(u8),
+
+    /// Driving the RSTN line failed.
+    ResetPin,
+}
+
+impl<I2cError> Error<I2cError> {
+    /// Gets the access that failed on the bus, if the error comes from the bus.
+    pub fn context(&self) -> Option<&Context> {
+        match self {
+            Error::I2c { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// Gets the error reported by the bus, if any.
+    pub fn i2c_error(&self) -> Option<&I2cError> {
+        match self {
+            Error::I2c { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Access to the device during which a bus error occurred.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Context {
+    /// Register accessed, the first one for accesses to consecutive registers.
+    pub register: Register,
+    pub operation: Operation,
+    /// Number of transfers attempted, including the failed one.
+    pub attempts: u8,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Operation {
+    Read,
+    Write,
 }
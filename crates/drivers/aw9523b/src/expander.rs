@@ -0,0 +1,91 @@
+//! Implementation of the interface shared by the GPIO expander drivers.
+
+use embedded_hal_async::delay::DelayUs;
+use io_expander::{Direction, IoExpander, LedDimming};
+
+use crate::config::Aw9523bConfig;
+use crate::reset::ResetLine;
+use crate::{Aw9523b, AwError, Pin, PinMode, PinState, Port};
+
+impl From<PinState> for io_expander::PinState {
+    fn from(state: PinState) -> Self {
+        match state {
+            PinState::Low => io_expander::PinState::Low,
+            PinState::High => io_expander::PinState::High,
+        }
+    }
+}
+
+impl From<io_expander::PinState> for PinState {
+    fn from(state: io_expander::PinState) -> Self {
+        match state {
+            io_expander::PinState::Low => PinState::Low,
+            io_expander::PinState::High => PinState::High,
+        }
+    }
+}
+
+impl<I2C, RST, E> IoExpander for Aw9523b<I2C, RST>
+where
+    I2C: embedded_hal_async::i2c::I2c + embedded_hal::i2c::ErrorType<Error = E>,
+    RST: ResetLine,
+{
+    type Error = AwError<E>;
+    type Config = Aw9523bConfig;
+
+    async fn probe(&mut self) -> Result<(), Self::Error> {
+        Aw9523b::probe(self).await
+    }
+
+    async fn reset<D: DelayUs>(&mut self, delay: &mut D) -> Result<(), Self::Error> {
+        Aw9523b::reset(self, delay).await
+    }
+
+    async fn configure(&mut self, config: &Self::Config) -> Result<(), Self::Error> {
+        Aw9523b::configure(self, config).await
+    }
+
+    async fn read_port(&mut self, port: Port) -> Result<u8, Self::Error> {
+        Aw9523b::read_port(self, port).await
+    }
+
+    async fn write_port(&mut self, port: Port, value: u8) -> Result<(), Self::Error> {
+        self.set_port_output_state(port, value).await
+    }
+
+    async fn read_pin(&mut self, pin: Pin) -> Result<io_expander::PinState, Self::Error> {
+        Ok(Aw9523b::read_pin(self, pin).await?.into())
+    }
+
+    async fn write_pin(&mut self, pin: Pin, state: io_expander::PinState) -> Result<(), Self::Error> {
+        self.set_pin_output_state(pin, state.into()).await
+    }
+
+    /// Also switches LED-mode pins back to GPIO mode.
+    async fn set_pin_direction(&mut self, pin: Pin, direction: Direction) -> Result<(), Self::Error> {
+        let mode = match direction {
+            Direction::Input => PinMode::Input,
+            Direction::Output => PinMode::Output,
+        };
+        self.set_pin_config(pin, mode).await
+    }
+
+    async fn set_pin_interrupt(&mut self, pin: Pin, enabled: bool) -> Result<(), Self::Error> {
+        self.enable_pin_interrupt(pin, enabled).await
+    }
+}
+
+impl<I2C, RST, E> LedDimming for Aw9523b<I2C, RST>
+where
+    I2C: embedded_hal_async::i2c::I2c + embedded_hal::i2c::ErrorType<Error = E>,
+    RST: ResetLine,
+{
+    async fn set_pin_led_mode(&mut self, pin: Pin, enabled: bool) -> Result<(), Self::Error> {
+        let mode = if enabled { PinMode::Led } else { PinMode::Output };
+        self.set_pin_config(pin, mode).await
+    }
+
+    async fn set_led_level(&mut self, pin: Pin, level: u8) -> Result<(), Self::Error> {
+        self.set_pin_led_pwm(pin, level).await
+    }
+}
@@ -0,0 +1,142 @@
+//! Several AW9523B sharing a bus, seen as a single device.
+//!
+//! Up to four devices fit on a bus, one per [`Address`](crate::Address). An [`Aw9523bGroup`]
+//! numbers their pins consecutively, device after device, so that the pins of all the devices can
+//! be read and written through [`GroupPin`]s:
+//!
+//! ```ignore
+//! let mut io_expanders = Aw9523bGroup::new([
+//!     Aw9523b::new(I2cDevice::new(bus), Address::from_straps(PinState::Low, PinState::Low)),
+//!     Aw9523b::new(I2cDevice::new(bus), Address::from_straps(PinState::High, PinState::Low)),
+//! ]);
+//!
+//! let pressed = io_expanders.read_inputs().await? & BUTTONS_MASK;
+//! ```
+
+use crate::{Aw9523b, AwError, BasicOps, Pin, PinMode, PinState, Port, PortPin, Register};
+
+/// Number of pins of a single device.
+const PINS_PER_DEVICE: usize = 16;
+
+/// Number of devices fitting on a bus, which is also as many as their pins fit in a `u64` mask.
+pub const MAX_DEVICES: usize = 4;
+
+/// A pin of one of the devices of an [`Aw9523bGroup`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GroupPin {
+    /// Index of the device within the group.
+    pub device: usize,
+    pub pin: Pin,
+}
+
+impl GroupPin {
+    pub const fn new(device: usize, pin: Pin) -> Self {
+        Self { device, pin }
+    }
+
+    /// Gets the pin at a position in the pin space of a group.
+    ///
+    /// Each device has 16 pins, port 0 followed by port 1.
+    pub const fn from_index(index: usize) -> Self {
+        let port = if index % PINS_PER_DEVICE < 8 { Port::Port0 } else { Port::Port1 };
+        let pin = match index % 8 {
+            0 => PortPin::P0,
+            1 => PortPin::P1,
+            2 => PortPin::P2,
+            3 => PortPin::P3,
+            4 => PortPin::P4,
+            5 => PortPin::P5,
+            6 => PortPin::P6,
+            _ => PortPin::P7,
+        };
+        Self::new(index / PINS_PER_DEVICE, Pin(port, pin))
+    }
+
+    /// Gets the position of the pin in the pin space of a group.
+    pub const fn index(self) -> usize {
+        self.device * PINS_PER_DEVICE + self.pin.0 as usize * 8 + self.pin.1 as usize
+    }
+
+    /// Gets the bit of the pin in the masks used by [`Aw9523bGroup`].
+    ///
+    /// Panics if the device is past [`MAX_DEVICES`], its pins not fitting in the mask.
+    pub const fn mask(self) -> u64 {
+        assert!(self.device < MAX_DEVICES, "at most four AW9523B share a bus");
+        1 << self.index()
+    }
+}
+
+/// Devices on the same bus with their pins numbered as a single pin space.
+///
+/// Methods taking a [`GroupPin`] panic if its device is not part of the group.
+pub struct Aw9523bGroup<I2C, const N: usize> {
+    devices: [Aw9523b<I2C>; N],
+}
+
+impl<I2C, E, const N: usize> Aw9523bGroup<I2C, N>
+where
+    I2C: embedded_hal_async::i2c::I2c + embedded_hal::i2c::ErrorType<Error = E>,
+{
+    /// The pins of all the devices must fit in a `u64` mask.
+    const MAX_DEVICES: () = assert!(N <= MAX_DEVICES, "at most four AW9523B share a bus");
+
+    pub fn new(devices: [Aw9523b<I2C>; N]) -> Self {
+        let () = Self::MAX_DEVICES;
+        Self { devices }
+    }
+
+    /// Gets a device of the group, to access the features not covered by the group.
+    pub fn device(&mut self, device: usize) -> &mut Aw9523b<I2C> {
+        &mut self.devices[device]
+    }
+
+    pub fn devices(&mut self) -> &mut [Aw9523b<I2C>; N] {
+        &mut self.devices
+    }
+
+    /// Gives the devices back.
+    pub fn release(self) -> [Aw9523b<I2C>; N] {
+        self.devices
+    }
+
+    /// Checks that every device of the group answers.
+    pub async fn probe(&mut self) -> Result<(), AwError<E>> {
+        for device in self.devices.iter_mut() {
+            device.probe().await?;
+        }
+        Ok(())
+    }
+
+    pub async fn set_pin_config(&mut self, pin: GroupPin, mode: PinMode) -> Result<(), AwError<E>> {
+        self.devices[pin.device].set_pin_config(pin.pin, mode).await
+    }
+
+    pub async fn read_pin(&mut self, pin: GroupPin) -> Result<PinState, AwError<E>> {
+        self.devices[pin.device].read_pin(pin.pin).await
+    }
+
+    pub async fn set_pin_output_state(&mut self, pin: GroupPin, state: PinState) -> Result<(), AwError<E>> {
+        self.devices[pin.device].set_pin_output_state(pin.pin, state).await
+    }
+
+    pub async fn set_pin_led_pwm(&mut self, pin: GroupPin, pwm: u8) -> Result<(), AwError<E>> {
+        self.devices[pin.device].set_pin_led_pwm(pin.pin, pwm).await
+    }
+
+    pub async fn enable_pin_interrupt(&mut self, pin: GroupPin, enable: bool) -> Result<(), AwError<E>> {
+        self.devices[pin.device].enable_pin_interrupt(pin.pin, enable).await
+    }
+
+    /// Reads the inputs of every device, one bit per pin as given by [`GroupPin::mask`].
+    ///
+    /// Both ports of a device are read in a single transaction.
+    pub async fn read_inputs(&mut self) -> Result<u64, AwError<E>> {
+        let mut inputs = 0;
+        for (i, device) in self.devices.iter_mut().enumerate() {
+            let mut ports = [0u8; 2];
+            device.read_registers(Register::InputPort0, &mut ports).await?;
+            inputs |= (u16::from_le_bytes(ports) as u64) << (i * PINS_PER_DEVICE);
+        }
+        Ok(inputs)
+    }
+}
@@ -0,0 +1,199 @@
+//! Scanning of a key matrix wired to the pins of the AW9523B.
+//!
+//! Rows are driven low one at a time while the columns, pulled up externally, are read back. Rows
+//! that are not being scanned are left floating as inputs so that two keys pressed in the same
+//! column never short a high row to a low one. Between scans all the rows are driven low, which
+//! lets a key press on any column raise an interrupt:
+//!
+//! ```ignore
+//! const KEYPAD: Keypad<3, 4> = Keypad::new(ROWS, COLUMNS);
+//!
+//! KEYPAD.configure(&mut io_expander).await?;
+//! loop {
+//!     let scan = KEYPAD.scan(&mut io_expander).await?;
+//!     if !scan.is_ghosted() {
+//!         // Keys become buttons numbered after the other buttons of the board
+//!         let pressed: IdSet = scan.keys().iter().map(|key| Id(FIRST_KEY + key)).collect();
+//!         for (button, event) in buttons.process_input(pressed).iter() {
+//!             handle(button, event);
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Matrices without diodes cannot tell three keys pressed in the corners of a rectangle from four:
+//! the fourth one appears pressed as well. Such scans are reported as ghosted.
+
+use crate::register::{ConfigPort, Direction, IntPort, Interrupt, OutputPort, TypedRegister, WritableRegister};
+use crate::{Aw9523b, AwError, BasicOps, Pin, PinMode, PinState, Register};
+
+/// Keys pressed in a matrix, one bit per key numbered `row * columns + column`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KeySet(pub u64);
+
+impl KeySet {
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn contains(&self, key: usize) -> bool {
+        key < 64 && self.0 & (1 << key) != 0
+    }
+
+    pub fn insert(&mut self, key: usize) {
+        self.0 |= 1 << key;
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Gets the key pressed if exactly one is.
+    pub const fn single(&self) -> Option<usize> {
+        if self.len() == 1 {
+            Some(self.0.trailing_zeros() as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Iterates over the pressed keys, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> {
+        let bits = self.0;
+        (0..64).filter(move |&key| bits & (1 << key) != 0)
+    }
+}
+
+/// Result of a scan of the whole matrix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Scan {
+    keys: KeySet,
+    ghosted: bool,
+}
+
+impl Scan {
+    /// Keys seen pressed, which include phantom keys when the scan is ghosted.
+    pub fn keys(&self) -> KeySet {
+        self.keys
+    }
+
+    /// Checks if the pressed keys are ambiguous, in which case they should be ignored.
+    pub fn is_ghosted(&self) -> bool {
+        self.ghosted
+    }
+}
+
+/// Matrix of `R` rows and `C` columns of keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Keypad<const R: usize, const C: usize> {
+    rows: [Pin; R],
+    columns: [Pin; C],
+}
+
+impl<const R: usize, const C: usize> Keypad<R, C> {
+    /// Every key must fit in a [`KeySet`].
+    const MAX_KEYS: () = assert!(R * C <= 64, "a keypad has at most 64 keys");
+
+    /// The pressed columns of a row are kept in a `u16`, one bit per column.
+    const MAX_COLUMNS: () = assert!(C <= 16, "a keypad has at most 16 columns");
+
+    pub const fn new(rows: [Pin; R], columns: [Pin; C]) -> Self {
+        let () = Self::MAX_KEYS;
+        let () = Self::MAX_COLUMNS;
+        Self { rows, columns }
+    }
+
+    /// Gets the key at a row and column.
+    pub const fn key(row: usize, column: usize) -> usize {
+        row * C + column
+    }
+
+    /// Configures the columns as inputs with their interrupt enabled and drives all the rows low.
+    pub async fn configure<I2C, RST, E>(&self, driver: &mut Aw9523b<I2C, RST>) -> Result<(), AwError<E>>
+    where
+        I2C: embedded_hal_async::i2c::I2c + embedded_hal::i2c::ErrorType<Error = E>,
+    {
+        for &row in &self.rows {
+            driver.modify_typed(row.0, |outputs: &mut OutputPort| outputs.set(row.1, PinState::Low)).await?;
+            driver.set_pin_config(row, PinMode::Output).await?;
+            // Rows are switched between input and output while scanning
+            driver.modify_typed(row.0, |interrupts: &mut IntPort| interrupts.set(row.1, Interrupt::Disabled)).await?;
+        }
+
+        for &column in &self.columns {
+            driver.set_pin_config(column, PinMode::Input).await?;
+            driver.enable_pin_interrupt(column, true).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Scans every row of the matrix, in two transactions per row plus two to save and restore
+    /// the pin directions.
+    ///
+    /// The rows are left driven low afterwards.
+    pub async fn scan<I2C, RST, E>(&self, driver: &mut Aw9523b<I2C, RST>) -> Result<Scan, AwError<E>>
+    where
+        I2C: embedded_hal_async::i2c::I2c + embedded_hal::i2c::ErrorType<Error = E>,
+    {
+        let mut config = [0u8; 2];
+        driver.read_registers(Register::ConfigPort0, &mut config).await?;
+        let idle = config;
+
+        let mut pressed_columns = [0u16; R];
+        for (row, pressed) in self.rows.iter().zip(pressed_columns.iter_mut()) {
+            // Only the scanned row is an output
+            let mut scan_config = idle;
+            for other in &self.rows {
+                set_direction(&mut scan_config, *other, Direction::Input);
+            }
+            set_direction(&mut scan_config, *row, Direction::Output);
+            driver.write_registers(Register::ConfigPort0, &scan_config).await?;
+
+            let mut inputs = [0u8; 2];
+            driver.read_registers(Register::InputPort0, &mut inputs).await?;
+
+            for (column, pin) in self.columns.iter().enumerate() {
+                if inputs[pin.0 as usize] & (1 << pin.1 as u8) == 0 {
+                    *pressed |= 1 << column;
+                }
+            }
+        }
+
+        driver.write_registers(Register::ConfigPort0, &idle).await?;
+
+        let mut keys = KeySet::empty();
+        for (row, columns) in pressed_columns.iter().enumerate() {
+            for column in 0..C {
+                if columns & (1 << column) != 0 {
+                    keys.insert(Self::key(row, column));
+                }
+            }
+        }
+
+        Ok(Scan {
+            keys,
+            ghosted: is_ghosted(&pressed_columns),
+        })
+    }
+}
+
+fn set_direction(config: &mut [u8; 2], pin: Pin, direction: Direction) {
+    let port = pin.0 as usize;
+    let mut register = ConfigPort::from_raw(config[port]);
+    register.set(pin.1, direction);
+    config[port] = register.into_raw();
+}
+
+/// Two rows sharing two pressed columns form a rectangle, one of its corners possibly a phantom.
+fn is_ghosted(pressed_columns: &[u16]) -> bool {
+    pressed_columns.iter().enumerate().any(|(i, a)| {
+        pressed_columns[i + 1..]
+            .iter()
+            .any(|b| (a & b).count_ones() >= 2)
+    })
+}
@@ -1,29 +1,101 @@
 #![no_std]
 #![feature(async_fn_in_trait)]
 
-use register::Register;
+use embedded_hal::i2c::Operation as I2cOperation;
+use error::{Context, Operation};
+use recovery::Shadow;
+use register::{
+    ConfigPort, Ctl, Direction, InputPort, IntPort, Interrupt, LedMode, LedModeSwitch, OutputPort, TypedRegister,
+    WritableRegister,
+};
+pub use register::Register;
+pub use address::Address;
+pub use pin_set::PinSet;
+pub use io_expander::{Pin, Port, PortPin};
 pub use error::Error as AwError;
-
-mod register;
-mod error;
-
-pub struct Aw9523b<I2C> {
+pub use retry::RetryPolicy;
+
+pub mod register;
+pub mod error;
+mod address;
+mod expander;
+mod pin_set;
+mod retry;
+
+pub mod animation;
+pub mod blocking;
+pub mod brightness;
+pub mod config;
+pub mod dump;
+pub mod group;
+pub mod keypad;
+pub mod recovery;
+pub mod reset;
+
+#[cfg(feature = "sim")]
+pub mod sim;
+
+pub struct Aw9523b<I2C, RST = NoResetPin> {
     i2c: I2C,
     addr: u8,
+    shadow: Shadow,
+    reset_pin: RST,
+    retry_policy: RetryPolicy,
 }
 
+/// Placeholder for drivers that do not control the RSTN line of the device.
+pub struct NoResetPin;
+
 impl<I2C, E> Aw9523b<I2C>
 where
     I2C: embedded_hal_async::i2c::I2c + embedded_hal::i2c::ErrorType<Error = E>,
 {
-    /// Creates a new instance of an AW9523B driver.
-    pub fn new(i2c: I2C, addr: u8) -> Self {
+    /// Creates a new instance of an AW9523B driver, at a raw 7-bit address or an [`Address`].
+    pub fn new(i2c: I2C, addr: impl Into<u8>) -> Self {
         Self {
             i2c,
-            addr,
+            addr: addr.into(),
+            shadow: Shadow::new(),
+            reset_pin: NoResetPin,
+            retry_policy: RetryPolicy::NEVER,
         }
     }
 
+    /// Gives the driver control over the RSTN line of the device.
+    pub fn with_reset_pin<RST>(self, reset_pin: RST) -> Aw9523b<I2C, RST>
+    where
+        RST: embedded_hal::digital::OutputPin,
+    {
+        Aw9523b {
+            i2c: self.i2c,
+            addr: self.addr,
+            shadow: self.shadow,
+            reset_pin,
+            retry_policy: self.retry_policy,
+        }
+    }
+}
+
+impl<I2C, RST> Aw9523b<I2C, RST> {
+    /// Sets the bus errors retried before an access fails.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = retry_policy;
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+}
+
+impl<I2C, RST, E> Aw9523b<I2C, RST>
+where
+    I2C: embedded_hal_async::i2c::I2c + embedded_hal::i2c::ErrorType<Error = E>,
+{
     /// Sends a command to perform a software reset.
     pub async fn software_reset(&mut self) -> Result<(), AwError<E>> {
         self.write_register(Register::SwRstn, 0x00).await
@@ -31,173 +103,85 @@ where
 
     /// Reads the port input state.
     pub async fn read_port(&mut self, port: Port) -> Result<u8, AwError<E>> {
-        let register = match port {
-            Port::Port0 => Register::InputPort0,
-            Port::Port1 => Register::InputPort1,
-        };
-        self.read_register(register).await
+        self.read_register(InputPort::register(port)).await
     }
 
     pub async fn read_pin(&mut self, pin: Pin) -> Result<PinState, AwError<E>> {
-        let port_state = self.read_port(pin.0).await?;
-        let pin_state = ((port_state >> pin.1 as u8) & 0x01) != 0;
-        Ok(pin_state.into())
+        let input: InputPort = self.read_typed(pin.0).await?;
+        Ok(input.get(pin.1))
     }
 
     pub async fn get_port_output_state(&mut self, port: Port) -> Result<u8, AwError<E>> {
-        let register = match port {
-            Port::Port0 => Register::OutputPort0,
-            Port::Port1 => Register::OutputPort1,
-        };
-        self.read_register(register).await
+        self.read_register(OutputPort::register(port)).await
     }
 
     pub async fn set_port_output_state(&mut self, port: Port, value: u8) -> Result<(), AwError<E>> {
-        let register = match port {
-            Port::Port0 => Register::OutputPort0,
-            Port::Port1 => Register::OutputPort1,
-        };
-        self.write_register(register, value).await
+        self.write_register(OutputPort::register(port), value).await
     }
 
     pub async fn get_pin_output_state(&mut self, pin: Pin) -> Result<PinState, AwError<E>> {
-        let port_state = self.get_port_output_state(pin.0).await?;
-        let pin_state = ((port_state >> pin.1 as u8) & 0x01) != 0;
-        Ok(pin_state.into())
+        let output: OutputPort = self.read_typed(pin.0).await?;
+        Ok(output.get(pin.1))
     }
 
     pub async fn set_pin_output_state(&mut self, pin: Pin, state: PinState) -> Result<(), AwError<E>> {
-        let register = match pin.0 {
-            Port::Port0 => Register::OutputPort0,
-            Port::Port1 => Register::OutputPort1,
-        };
-
-        let bits = 1 << pin.1 as u8;
-
-        match state {
-            PinState::Low => self.clear_register_bits(register, bits).await,
-            PinState::High => self.set_register_bits(register, bits).await,
-        }
+        self.modify_typed(pin.0, |output: &mut OutputPort| output.set(pin.1, state)).await
     }
 
     pub async fn get_port_config(&mut self, port: Port) -> Result<u8, AwError<E>> {
-        let register = match port {
-            Port::Port0 => Register::ConfigPort0,
-            Port::Port1 => Register::ConfigPort1,
-        };
-        self.read_register(register).await
+        self.read_register(ConfigPort::register(port)).await
     }
 
     pub async fn set_port_config(&mut self, port: Port, value: u8) -> Result<(), AwError<E>> {
-        let register = match port {
-            Port::Port0 => Register::ConfigPort0,
-            Port::Port1 => Register::ConfigPort1,
-        };
-        self.write_register(register, value).await
+        self.write_register(ConfigPort::register(port), value).await
     }
 
     pub async fn get_pin_config(&mut self, pin: Pin) -> Result<PinMode, AwError<E>> {
-        let port_config = self.get_port_config(pin.0).await?;
-        let led_mode_switch = self.get_port_led_mode_switch(pin.0).await?;
-        let pin_config = ((port_config >> pin.1 as u8) & 0x01) != 0;
-        let pin_led_mode_switch = (led_mode_switch >> pin.1 as u8) != 0;
-
-        let pin_mode = match (pin_config, pin_led_mode_switch) {
-            (true, _) => PinMode::Input,
-            (false, true) => PinMode::Output,
-            (false, false) => PinMode::Led,
+        let config: ConfigPort = self.read_typed(pin.0).await?;
+        let led_mode_switch: LedModeSwitch = self.read_typed(pin.0).await?;
+
+        let pin_mode = match (config.get(pin.1), led_mode_switch.get(pin.1)) {
+            (Direction::Input, _) => PinMode::Input,
+            (Direction::Output, LedMode::Gpio) => PinMode::Output,
+            (Direction::Output, LedMode::Led) => PinMode::Led,
         };
 
         Ok(pin_mode)
     }
 
     pub async fn set_pin_config(&mut self, pin: Pin, mode: PinMode) -> Result<(), AwError<E>> {
-        let config_register = match pin.0 {
-            Port::Port0 => Register::ConfigPort0,
-            Port::Port1 => Register::ConfigPort1,
-        };
-
-        let led_mode_switch_register = match pin.0 {
-            Port::Port0 => Register::LedModeSwitchP0,
-            Port::Port1 => Register::LedModeSwitchP1,
+        let (direction, led_mode) = match mode {
+            PinMode::Input => (Direction::Input, LedMode::Gpio),
+            PinMode::Output => (Direction::Output, LedMode::Gpio),
+            PinMode::Led => (Direction::Output, LedMode::Led),
         };
 
-        let bits = 1 << pin.1 as u8;
-
-        match mode {
-            PinMode::Input => {
-                self.set_register_bits(config_register, bits).await?;
-                self.set_register_bits(led_mode_switch_register, bits).await
-            },
-            PinMode::Output => {
-                self.clear_register_bits(config_register, bits).await?;
-                self.set_register_bits(led_mode_switch_register, bits).await
-            },
-            PinMode::Led => {
-                self.clear_register_bits(config_register, bits).await?;
-                self.clear_register_bits(led_mode_switch_register, bits).await
-            },
-        }
+        self.modify_typed(pin.0, |config: &mut ConfigPort| config.set(pin.1, direction)).await?;
+        self.modify_typed(pin.0, |led_mode_switch: &mut LedModeSwitch| led_mode_switch.set(pin.1, led_mode)).await
     }
 
     pub async fn set_pin_led_pwm(&mut self, pin: Pin, pwm: u8) -> Result<(), AwError<E>> {
-        let dim_registers = [
-            Register::Dim4,     // P0.0
-            Register::Dim5,     // P0.1
-            Register::Dim6,     // P0.2
-            Register::Dim7,     // P0.3
-            Register::Dim8,     // P0.4
-            Register::Dim9,     // P0.5
-            Register::Dim10,    // P0.6
-            Register::Dim11,    // P0.7
-            Register::Dim0,     // P1.0
-            Register::Dim1,     // P1.1
-            Register::Dim2,     // P1.2
-            Register::Dim3,     // P1.3
-            Register::Dim12,    // P1.4
-            Register::Dim13,    // P1.5
-            Register::Dim14,    // P1.6
-            Register::Dim15,    // P1.7
-        ];
-
-        let register = dim_registers[(pin.0 as usize * 8) + pin.1 as usize];
-        self.write_register(register, pwm).await
+        self.write_register(dim_register(pin), pwm).await
     }
 
     pub async fn get_port_interrupt_config(&mut self, port: Port) -> Result<u8, AwError<E>> {
-        let register = match port {
-            Port::Port0 => Register::IntPort0,
-            Port::Port1 => Register::IntPort1,
-        };
-        self.read_register(register).await
+        self.read_register(IntPort::register(port)).await
     }
 
     pub async fn set_port_interrupt_config(&mut self, port: Port, value: u8) -> Result<(), AwError<E>> {
-        let register = match port {
-            Port::Port0 => Register::IntPort0,
-            Port::Port1 => Register::IntPort1,
-        };
-        self.write_register(register, value).await
+        self.write_register(IntPort::register(port), value).await
     }
 
+    /// Checks if the interrupt of a pin is enabled.
     pub async fn get_pin_interrupt_config(&mut self, pin: Pin) -> Result<bool, AwError<E>> {
-        let port_config = self.get_port_interrupt_config(pin.0).await?;
-        let pin_config = ((port_config >> pin.1 as u8) & 0x01) != 0;
-        Ok(pin_config.into())
+        let interrupts: IntPort = self.read_typed(pin.0).await?;
+        Ok(interrupts.get(pin.1) == Interrupt::Enabled)
     }
 
+    /// Enables or disables the interrupt of a pin.
     pub async fn enable_pin_interrupt(&mut self, pin: Pin, enable: bool) -> Result<(), AwError<E>> {
-        let register = match pin.0 {
-            Port::Port0 => Register::IntPort0,
-            Port::Port1 => Register::IntPort1,
-        };
-
-        let bits = 1 << pin.1 as u8;
-
-        match enable {
-            true => self.clear_register_bits(register, bits).await,
-            false => self.set_register_bits(register, bits).await,
-        }
+        let interrupt = if enable { Interrupt::Enabled } else { Interrupt::Disabled };
+        self.modify_typed(pin.0, |interrupts: &mut IntPort| interrupts.set(pin.1, interrupt)).await
     }
 
     pub async fn read_device_id(&mut self) -> Result<u8, AwError<E>> {
@@ -205,65 +189,70 @@ where
     }
 
     pub async fn get_port0_drive_mode(&mut self) -> Result<Port0OutputDriveMode, AwError<E>> {
-        let value = self.read_register(Register::Ctl).await?;
-        let port0_drive_mode = ((value >> 4) & 0x01) != 0;
-        Ok(port0_drive_mode.into())
+        let ctl: Ctl = self.read_typed(()).await?;
+        Ok(ctl.port0_drive_mode())
     }
 
     pub async fn set_port0_drive_mode(&mut self, mode: Port0OutputDriveMode) -> Result<(), AwError<E>> {
-        match mode {
-            Port0OutputDriveMode::OpenDrain => self.clear_register_bits(Register::Ctl, 0x10).await,
-            Port0OutputDriveMode::PushPull => self.set_register_bits(Register::Ctl, 0x10).await,
-        }
+        self.modify_typed((), |ctl: &mut Ctl| ctl.set_port0_drive_mode(mode)).await
     }
 
     pub async fn get_drive_current(&mut self) -> Result<DriveCurrent, AwError<E>> {
-        let value = self.read_register(Register::Ctl).await?;
-        let drive_current = value & 0x03;
-        let drive_current = match drive_current {
-            0 => DriveCurrent::Max,
-            1 => DriveCurrent::High,
-            2 => DriveCurrent::Mid,
-            3 => DriveCurrent::Low,
-            _ => unreachable!(),
-        };
-        Ok(drive_current)
+        let ctl: Ctl = self.read_typed(()).await?;
+        Ok(ctl.drive_current())
     }
 
     pub async fn set_drive_current(&mut self, drive_current: DriveCurrent) -> Result<(), AwError<E>> {
-        let f = |v| {
-            let other_bits = v & !0x03;
-            other_bits | drive_current as u8
-        };
-        self.modify_register(Register::Ctl, f).await
+        self.modify_typed((), |ctl: &mut Ctl| ctl.set_drive_current(drive_current)).await
     }
 
     pub async fn get_port_led_mode_switch(&mut self, port: Port) -> Result<u8, AwError<E>> {
-        let register = match port {
-            Port::Port0 => Register::LedModeSwitchP0,
-            Port::Port1 => Register::LedModeSwitchP1,
-        };
-        self.read_register(register).await
+        self.read_register(LedModeSwitch::register(port)).await
     }
 
     pub async fn set_port_led_mode_switch(&mut self, port: Port, value: u8) -> Result<(), AwError<E>> {
-        let register = match port {
-            Port::Port0 => Register::LedModeSwitchP0,
-            Port::Port1 => Register::LedModeSwitchP1,
-        };
-        self.write_register(register, value).await
+        self.write_register(LedModeSwitch::register(port), value).await
     }
 }
 
+/// Runs a bus transfer until it succeeds or the retry policy of the driver gives up, attaching the
+/// accessed register to the final error.
+macro_rules! with_retries {
+    ($driver:ident, $register:expr, $operation:expr, $transfer:expr) => {{
+        let mut attempts: u8 = 0;
+        loop {
+            attempts = attempts.saturating_add(1);
+            match $transfer.await {
+                Ok(value) => break Ok(value),
+                Err(error) if $driver.retry_policy.should_retry(retry::error_kind(&$driver.i2c, &error), attempts) => continue,
+                Err(error) => {
+                    let context = Context {
+                        register: $register,
+                        operation: $operation,
+                        attempts,
+                    };
+                    break Err(AwError::I2c { error, context });
+                }
+            }
+        }
+    }};
+}
+
 pub trait BasicOps {
     type Error;
 
     /// Writes a value to a given register.
     async fn write_register(&mut self, register: Register, value: u8) -> Result<(), AwError<Self::Error>>;
 
+    /// Writes consecutive registers in one transaction, starting at the given register.
+    async fn write_registers(&mut self, register: Register, values: &[u8]) -> Result<(), AwError<Self::Error>>;
+
     /// Reads the value from the given register.
     async fn read_register(&mut self, register: Register) -> Result<u8, AwError<Self::Error>>;
 
+    /// Reads consecutive registers in one transaction, starting at the given register.
+    async fn read_registers(&mut self, register: Register, values: &mut [u8]) -> Result<(), AwError<Self::Error>>;
+
     /// Modifies the value of a given register.
     async fn modify_register<F>(&mut self, register: Register, f: F) -> Result<(), AwError<Self::Error>>
     where
@@ -282,56 +271,154 @@ pub trait BasicOps {
     async fn clear_register_bits(&mut self, register: Register, bits: u8) -> Result<(), AwError<Self::Error>> {
         self.modify_register(register, |v| v & !bits).await
     }
+
+    /// Reads a register as its typed representation.
+    async fn read_typed<R>(&mut self, instance: R::Instance) -> Result<R, AwError<Self::Error>>
+    where
+        R: TypedRegister,
+    {
+        let value = self.read_register(R::register(instance)).await?;
+        Ok(R::from_raw(value))
+    }
+
+    /// Writes a register from its typed representation.
+    async fn write_typed<R>(&mut self, instance: R::Instance, value: R) -> Result<(), AwError<Self::Error>>
+    where
+        R: WritableRegister,
+    {
+        self.write_register(R::register(instance), value.into_raw()).await
+    }
+
+    /// Modifies the fields of a register through its typed representation.
+    async fn modify_typed<R, F>(&mut self, instance: R::Instance, f: F) -> Result<(), AwError<Self::Error>>
+    where
+        R: WritableRegister,
+        F: FnOnce(&mut R),
+    {
+        let mut value: R = self.read_typed(instance).await?;
+        f(&mut value);
+        self.write_typed(instance, value).await
+    }
 }
 
-impl<I2C, E> BasicOps for Aw9523b<I2C>
+impl<I2C, RST, E> BasicOps for Aw9523b<I2C, RST>
 where
     I2C: embedded_hal_async::i2c::I2c + embedded_hal::i2c::ErrorType<Error = E>,
 {
     type Error = E;
     async fn write_register(&mut self, register: Register, value: u8) -> Result<(), AwError<Self::Error>> {
         if register.is_read_only() {
-            return Err(AwError::WriteToReadOnly);
+            return Err(AwError::WriteToReadOnly(register));
         }
 
-        self.i2c.write(self.addr, &[register.addr(), value]).await.map_err(AwError::I2c)?;
+        with_retries!(self, register, Operation::Write, self.i2c.write(self.addr, &[register.addr(), value]))?;
+        self.shadow.record(register, &[value]);
+        Ok(())
+    }
+
+    async fn write_registers(&mut self, register: Register, values: &[u8]) -> Result<(), AwError<Self::Error>> {
+        // The register address auto-increments after every byte written
+        let is_read_only = |offset| {
+            register.addr().checked_add(offset)
+                .and_then(Register::from_addr)
+                .is_some_and(Register::is_read_only)
+        };
+        if let Some(read_only) = (0..values.len() as u8).find(|&offset| is_read_only(offset)) {
+            let read_only = Register::from_addr(register.addr() + read_only).unwrap_or(register);
+            return Err(AwError::WriteToReadOnly(read_only));
+        }
+
+        let pointer = [register.addr()];
+        with_retries!(
+            self,
+            register,
+            Operation::Write,
+            self.i2c.transaction(self.addr, &mut [I2cOperation::Write(&pointer), I2cOperation::Write(values)])
+        )?;
+        self.shadow.record(register, values);
         Ok(())
     }
 
     async fn read_register(&mut self, register: Register) -> Result<u8, AwError<Self::Error>> {
         let mut buffer = [0u8; 1];
-        self.i2c.write_read(self.addr, &[register.addr()], &mut buffer).await.map_err(AwError::I2c)?;
+        with_retries!(self, register, Operation::Read, self.i2c.write_read(self.addr, &[register.addr()], &mut buffer))?;
         Ok(buffer[0])
     }
+
+    async fn read_registers(&mut self, register: Register, values: &mut [u8]) -> Result<(), AwError<Self::Error>> {
+        with_retries!(self, register, Operation::Read, self.i2c.write_read(self.addr, &[register.addr()], values))?;
+        Ok(())
+    }
 }
 
-pub struct Pin(pub Port, pub PortPin);
+/// Number of dimming registers, `DIM0..=DIM15`.
+pub(crate) const DIM_COUNT: usize = 16;
+
+/// Gets the dimming register controlling the LED current of a pin.
+pub const fn dim_register(pin: Pin) -> Register {
+    let dim_registers = [
+        Register::Dim4,     // P0.0
+        Register::Dim5,     // P0.1
+        Register::Dim6,     // P0.2
+        Register::Dim7,     // P0.3
+        Register::Dim8,     // P0.4
+        Register::Dim9,     // P0.5
+        Register::Dim10,    // P0.6
+        Register::Dim11,    // P0.7
+        Register::Dim0,     // P1.0
+        Register::Dim1,     // P1.1
+        Register::Dim2,     // P1.2
+        Register::Dim3,     // P1.3
+        Register::Dim12,    // P1.4
+        Register::Dim13,    // P1.5
+        Register::Dim14,    // P1.6
+        Register::Dim15,    // P1.7
+    ];
+
+    dim_registers[(pin.0 as usize * 8) + pin.1 as usize]
+}
 
-#[derive(Clone, Copy)]
-pub enum Port {
-    Port0,
-    Port1,
+/// Gets the offset of the dimming register of a pin from `DIM0`.
+pub(crate) const fn dim_index(pin: Pin) -> usize {
+    (dim_register(pin) as u8 - Register::Dim0 as u8) as usize
 }
 
-#[derive(Clone, Copy)]
-#[repr(u8)]
-pub enum PortPin {
-    P0,
-    P1,
-    P2,
-    P3,
-    P4,
-    P5,
-    P6,
-    P7,
+/// Registers not needing a write between two runs that are cheaper to rewrite than to start a new
+/// transaction for.
+const MAX_MERGED_GAP: usize = 2;
+
+/// Gets the next run of registers within `from..end` to write in a single transaction, starting
+/// and ending with a register needing a write.
+pub(crate) fn next_write_run(from: usize, end: usize, needs_write: impl Fn(usize) -> bool) -> Option<(usize, usize)> {
+    let start = (from..end).find(|&i| needs_write(i))?;
+
+    let mut run_end = start + 1;
+    let mut gap = 0;
+    for i in start + 1..end {
+        if needs_write(i) {
+            run_end = i + 1;
+            gap = 0;
+        } else {
+            gap += 1;
+            if gap > MAX_MERGED_GAP {
+                break;
+            }
+        }
+    }
+
+    Some((start, run_end))
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum PinMode {
     Input,
     Output,
     Led,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum PinState {
     /// Low logic level.
     Low,
@@ -343,13 +430,15 @@ pub enum PinState {
 impl From<bool> for PinState {
     fn from(value: bool) -> Self {
         if value {
-            PinState::Low
-        } else {
             PinState::High
+        } else {
+            PinState::Low
         }
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Port0OutputDriveMode {
     /// Pins of port 0 set to open-drain mode.
     OpenDrain,
@@ -361,23 +450,26 @@ pub enum Port0OutputDriveMode {
 impl From<bool> for Port0OutputDriveMode {
     fn from(value: bool) -> Self {
         if value {
-            Port0OutputDriveMode::OpenDrain
-        } else {
             Port0OutputDriveMode::PushPull
+        } else {
+            Port0OutputDriveMode::OpenDrain
         }
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
 pub enum DriveCurrent {
     /// I_max set to maximum of 37 mA.
-    Max,
+    Max = 0,
 
     /// I_max limited to 3/4 of I_max.
-    High,
+    High = 1,
 
     /// I_max limited to 1/2 of I_max.
-    Mid,
+    Mid = 2,
 
     /// I_max limited to 1/4 of I_max.
-    Low,
+    Low = 3,
 }
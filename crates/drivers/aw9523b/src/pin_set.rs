@@ -0,0 +1,183 @@
+//! Updates of several pins at once, across both ports.
+//!
+//! A [`PinSet`] holds one bit per pin, port 0 in the low byte and port 1 in the high byte. Each
+//! update reads the registers of the two ports in one transaction, unless the driver already knows
+//! their value, and writes back the ports that changed in one more:
+//!
+//! ```ignore
+//! const ENABLES: PinSet = PinSet::empty().with(AMP_ENABLE).with(DAC_ENABLE);
+//!
+//! io_expander.configure_pins(ENABLES, PinMode::Output).await?;
+//! io_expander.set_pins(ENABLES).await?;
+//! ```
+
+use core::ops::{BitAnd, BitOr, Not};
+
+use crate::{Aw9523b, AwError, BasicOps, Pin, PinMode, Port, Register};
+
+/// A set of pins of the device, as a 16-bit mask.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PinSet(pub u16);
+
+impl PinSet {
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn all() -> Self {
+        Self(0xFFFF)
+    }
+
+    /// Gets the set made of the pins of a port set in a mask.
+    pub const fn from_port(port: Port, mask: u8) -> Self {
+        Self((mask as u16) << (port as u16 * 8))
+    }
+
+    /// Adds a pin to the set, for use in constants.
+    pub const fn with(self, pin: Pin) -> Self {
+        Self(self.0 | Self::bit(pin))
+    }
+
+    pub const fn contains(&self, pin: Pin) -> bool {
+        self.0 & Self::bit(pin) != 0
+    }
+
+    pub fn insert(&mut self, pin: Pin) {
+        self.0 |= Self::bit(pin);
+    }
+
+    pub fn remove(&mut self, pin: Pin) {
+        self.0 &= !Self::bit(pin);
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Gets the mask of the pins of a port in the set.
+    pub const fn port(&self, port: Port) -> u8 {
+        (self.0 >> (port as u16 * 8)) as u8
+    }
+
+    const fn bit(pin: Pin) -> u16 {
+        1 << (pin.0 as u16 * 8 + pin.1 as u16)
+    }
+}
+
+impl From<Pin> for PinSet {
+    fn from(pin: Pin) -> Self {
+        Self::empty().with(pin)
+    }
+}
+
+impl BitOr for PinSet {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for PinSet {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl Not for PinSet {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self(!self.0)
+    }
+}
+
+impl<I2C, RST, E> Aw9523b<I2C, RST>
+where
+    I2C: embedded_hal_async::i2c::I2c + embedded_hal::i2c::ErrorType<Error = E>,
+{
+    /// Drives the outputs of a set of pins high, in at most two transactions.
+    pub async fn set_pins(&mut self, pins: PinSet) -> Result<(), AwError<E>> {
+        self.modify_ports(Register::OutputPort0, pins, |outputs| outputs | pins.0).await
+    }
+
+    /// Drives the outputs of a set of pins low, in at most two transactions.
+    pub async fn clear_pins(&mut self, pins: PinSet) -> Result<(), AwError<E>> {
+        self.modify_ports(Register::OutputPort0, pins, |outputs| outputs & !pins.0).await
+    }
+
+    /// Sets the outputs of a set of pins to the matching bits of `levels`, in at most two
+    /// transactions.
+    pub async fn write_pins(&mut self, pins: PinSet, levels: PinSet) -> Result<(), AwError<E>> {
+        self.modify_ports(Register::OutputPort0, pins, |outputs| (outputs & !pins.0) | (levels.0 & pins.0))
+            .await
+    }
+
+    /// Sets the mode of a set of pins, in at most four transactions.
+    pub async fn configure_pins(&mut self, pins: PinSet, mode: PinMode) -> Result<(), AwError<E>> {
+        // A set bit makes the pin an input, respectively a GPIO
+        let (input, gpio) = match mode {
+            PinMode::Input => (true, true),
+            PinMode::Output => (false, true),
+            PinMode::Led => (false, false),
+        };
+        let apply = |value: u16, set: bool| if set { value | pins.0 } else { value & !pins.0 };
+
+        self.modify_ports(Register::ConfigPort0, pins, |config| apply(config, input)).await?;
+        self.modify_ports(Register::LedModeSwitchP0, pins, |led_mode_switch| apply(led_mode_switch, gpio))
+            .await
+    }
+
+    /// Modifies the pins of a set in a pair of port registers, `first` being the port 0 one.
+    ///
+    /// The registers are only read if their value is unknown, and only written if they change.
+    async fn modify_ports<F>(&mut self, first: Register, pins: PinSet, f: F) -> Result<(), AwError<E>>
+    where
+        F: FnOnce(u16) -> u16,
+    {
+        let affected = [pins.port(Port::Port0) != 0, pins.port(Port::Port1) != 0];
+        let Some((start, end)) = port_range(affected) else {
+            return Ok(());
+        };
+
+        let addr = first.addr() as usize;
+        let mut current = [0u8; 2];
+        let mut known = true;
+        for (port, value) in current.iter_mut().enumerate().take(end + 1).skip(start) {
+            match self.shadow.known_value(addr + port) {
+                Some(known_value) => *value = known_value,
+                None => known = false,
+            }
+        }
+        if !known {
+            let register = Register::from_addr(first.addr() + start as u8).unwrap_or(first);
+            self.read_registers(register, &mut current[start..=end]).await?;
+        }
+
+        let new = f(u16::from_le_bytes(current)).to_le_bytes();
+        let changed = [new[0] != current[0] && affected[0], new[1] != current[1] && affected[1]];
+        let Some((start, end)) = port_range(changed) else {
+            return Ok(());
+        };
+
+        let register = Register::from_addr(first.addr() + start as u8).unwrap_or(first);
+        self.write_registers(register, &new[start..=end]).await
+    }
+}
+
+/// Gets the first and last of the flagged ports, if any.
+fn port_range(ports: [bool; 2]) -> Option<(usize, usize)> {
+    match ports {
+        [true, true] => Some((0, 1)),
+        [true, false] => Some((0, 0)),
+        [false, true] => Some((1, 1)),
+        [false, false] => None,
+    }
+}
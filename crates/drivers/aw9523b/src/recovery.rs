@@ -0,0 +1,159 @@
+//! Presence checks and recovery from unexpected device resets.
+//!
+//! A brown-out or a glitch on the reset line silently brings the AW9523B back to its default
+//! register values. The driver keeps a shadow copy of every register written through it since the
+//! last software reset, which [`Aw9523b::check_and_recover`] compares against the device and writes
+//! back when they differ.
+//!
+//! Only the readable registers can be compared. This is enough in practice: the dimming registers,
+//! which are write-only, only have an effect once the LED mode switch of their pin was changed from
+//! its default value, and that change is detected.
+
+use crate::{Aw9523b, AwError, BasicOps, Register};
+
+/// Value of the `ID` register of every AW9523B.
+pub const DEVICE_ID: u8 = 0x23;
+
+/// Registers `0x00..=0x2F`, the dimming registers being the last ones.
+pub(crate) const SHADOW_SIZE: usize = 0x30;
+
+/// Readable registers holding configuration, as `(first, count)` ranges.
+pub(crate) const READABLE_RANGES: [(Register, usize); 2] = [(Register::OutputPort0, 6), (Register::Ctl, 3)];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Health {
+    /// The device matches the configuration written to it.
+    Ok,
+
+    /// The device lost its configuration, which was written again.
+    Restored,
+}
+
+/// Registers written since the last software reset.
+#[derive(Clone, Debug)]
+pub(crate) struct Shadow {
+    values: [u8; SHADOW_SIZE],
+    written: u64,
+    /// Whether the registers not written yet hold their default value.
+    reset: bool,
+}
+
+impl Shadow {
+    /// Creates a shadow of a device in an unknown state.
+    pub(crate) const fn new() -> Self {
+        Self {
+            values: [0; SHADOW_SIZE],
+            written: 0,
+            reset: false,
+        }
+    }
+
+    /// Creates a shadow of a device that was just reset.
+    pub(crate) const fn reset() -> Self {
+        Self {
+            reset: true,
+            ..Self::new()
+        }
+    }
+
+    /// Records consecutive values written starting at the given register.
+    pub(crate) fn record(&mut self, register: Register, values: &[u8]) {
+        if register == Register::SwRstn {
+            if values.first() == Some(&0x00) {
+                *self = Self::reset();
+            }
+            return;
+        }
+
+        for (addr, &value) in (register.addr() as usize..SHADOW_SIZE).zip(values) {
+            self.values[addr] = value;
+            self.written |= 1 << addr;
+        }
+    }
+
+    fn is_written(&self, addr: usize) -> bool {
+        self.written & (1 << addr) != 0
+    }
+
+    /// Gets the value a register is known to hold, if any.
+    pub(crate) fn known_value(&self, addr: usize) -> Option<u8> {
+        if self.is_written(addr) {
+            return Some(self.values[addr]);
+        }
+        if !self.reset {
+            return None;
+        }
+
+        match Register::from_addr(addr as u8)? {
+            // The reset value of port 0 depends on the AD0/AD1 straps
+            Register::OutputPort0 => None,
+            Register::LedModeSwitchP0 | Register::LedModeSwitchP1 => Some(0xFF),
+            _ => Some(0x00),
+        }
+    }
+
+    /// Gets the next run of written registers, starting the search at `from`.
+    fn next_run(&self, from: usize) -> Option<(usize, usize)> {
+        let start = (from..SHADOW_SIZE).find(|&addr| self.is_written(addr))?;
+        let end = (start..SHADOW_SIZE).find(|&addr| !self.is_written(addr)).unwrap_or(SHADOW_SIZE);
+        Some((start, end))
+    }
+}
+
+impl<I2C, RST, E> Aw9523b<I2C, RST>
+where
+    I2C: embedded_hal_async::i2c::I2c + embedded_hal::i2c::ErrorType<Error = E>,
+{
+    /// Checks that an AW9523B answers at the address of the driver.
+    pub async fn probe(&mut self) -> Result<(), AwError<E>> {
+        let id = self.read_device_id().await?;
+        if id != DEVICE_ID {
+            return Err(AwError::UnexpectedDeviceId(id));
+        }
+        Ok(())
+    }
+
+    /// Checks whether the device still holds the configuration written to it.
+    pub async fn is_configured(&mut self) -> Result<bool, AwError<E>> {
+        for (first, count) in READABLE_RANGES {
+            let mut values = [0u8; 6];
+            let values = &mut values[..count];
+            self.read_registers(first, values).await?;
+
+            let start = first.addr() as usize;
+            let matches = (start..start + count)
+                .zip(values.iter())
+                .all(|(addr, &value)| !self.shadow.is_written(addr) || self.shadow.values[addr] == value);
+
+            if !matches {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Writes every register written since the last software reset again.
+    pub async fn restore(&mut self) -> Result<(), AwError<E>> {
+        let mut from = 0;
+        while let Some((start, end)) = self.shadow.next_run(from) {
+            let register = Register::from_addr(start as u8).unwrap();
+            let values = self.shadow.values;
+            self.write_registers(register, &values[start..end]).await?;
+            from = end;
+        }
+        Ok(())
+    }
+
+    /// Probes the device and restores its configuration if it was reset behind the back of the driver.
+    pub async fn check_and_recover(&mut self) -> Result<Health, AwError<E>> {
+        self.probe().await?;
+
+        if self.is_configured().await? {
+            return Ok(Health::Ok);
+        }
+
+        self.restore().await?;
+        Ok(Health::Restored)
+    }
+}
@@ -1,5 +1,7 @@
+use crate::{DriveCurrent, PinState, Port, Port0OutputDriveMode, PortPin};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum Register {
     InputPort0 = 0x00,
@@ -39,6 +41,43 @@ impl Register {
         self as u8
     }
 
+    /// Gets the register at the given address, if any
+    pub fn from_addr(addr: u8) -> Option<Register> {
+        let register = match addr {
+            0x00 => Register::InputPort0,
+            0x01 => Register::InputPort1,
+            0x02 => Register::OutputPort0,
+            0x03 => Register::OutputPort1,
+            0x04 => Register::ConfigPort0,
+            0x05 => Register::ConfigPort1,
+            0x06 => Register::IntPort0,
+            0x07 => Register::IntPort1,
+            0x10 => Register::Id,
+            0x11 => Register::Ctl,
+            0x12 => Register::LedModeSwitchP0,
+            0x13 => Register::LedModeSwitchP1,
+            0x20 => Register::Dim0,
+            0x21 => Register::Dim1,
+            0x22 => Register::Dim2,
+            0x23 => Register::Dim3,
+            0x24 => Register::Dim4,
+            0x25 => Register::Dim5,
+            0x26 => Register::Dim6,
+            0x27 => Register::Dim7,
+            0x28 => Register::Dim8,
+            0x29 => Register::Dim9,
+            0x2A => Register::Dim10,
+            0x2B => Register::Dim11,
+            0x2C => Register::Dim12,
+            0x2D => Register::Dim13,
+            0x2E => Register::Dim14,
+            0x2F => Register::Dim15,
+            0x7F => Register::SwRstn,
+            _ => return None,
+        };
+        Some(register)
+    }
+
     /// Checks if the register is read-only
     pub fn is_read_only(self) -> bool {
         matches!(
@@ -48,4 +87,339 @@ impl Register {
             Register::Id
         )
     }
+
+    /// Checks if the register is write-only
+    pub fn is_write_only(self) -> bool {
+        matches!(
+            self,
+            Register::Dim0 |
+            Register::Dim1 |
+            Register::Dim2 |
+            Register::Dim3 |
+            Register::Dim4 |
+            Register::Dim5 |
+            Register::Dim6 |
+            Register::Dim7 |
+            Register::Dim8 |
+            Register::Dim9 |
+            Register::Dim10 |
+            Register::Dim11 |
+            Register::Dim12 |
+            Register::Dim13 |
+            Register::Dim14 |
+            Register::Dim15 |
+            Register::SwRstn
+        )
+    }
+}
+
+/// Value of a bit field inside a register.
+pub trait Field: Copy {
+    /// Decodes the field from its bits, already shifted down to bit 0.
+    fn from_bits(bits: u8) -> Self;
+
+    /// Encodes the field into its bits, starting at bit 0.
+    fn into_bits(self) -> u8;
+}
+
+/// Typed view of the content of a readable register.
+pub trait TypedRegister: Copy {
+    /// Selects between the instances of the register, e.g. the port it belongs to.
+    type Instance: Copy;
+
+    /// Gets the register holding the given instance.
+    fn register(instance: Self::Instance) -> Register;
+
+    /// Decodes the raw content of the register, dropping reserved bits.
+    fn from_raw(raw: u8) -> Self;
+}
+
+/// Typed register which can also be written.
+pub trait WritableRegister: TypedRegister {
+    /// Encodes the register into its raw content.
+    fn into_raw(self) -> u8;
+}
+
+/// Defines a register made of typed bit fields.
+///
+/// Each field is declared once with its getter, setter, type, offset and width, and bits not
+/// covered by any field are reserved and always written as 0.
+macro_rules! bitfield {
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident($register:ident) {
+            $(
+                $(#[$field_attr:meta])*
+                $get:ident, $set:ident: $ty:ty = $offset:literal, $width:literal;
+            )*
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+        pub struct $name(u8);
+
+        impl $name {
+            const FIELDS_MASK: u8 = 0 $(| (((1u16 << $width) - 1) << $offset) as u8)*;
+
+            $(
+                $(#[$field_attr])*
+                pub fn $get(&self) -> $ty {
+                    let mask = ((1u16 << $width) - 1) as u8;
+                    <$ty as Field>::from_bits((self.0 >> $offset) & mask)
+                }
+
+                $(#[$field_attr])*
+                pub fn $set(&mut self, value: $ty) {
+                    let mask = (((1u16 << $width) - 1) << $offset) as u8;
+                    self.0 = (self.0 & !mask) | ((Field::into_bits(value) << $offset) & mask);
+                }
+            )*
+        }
+
+        impl TypedRegister for $name {
+            type Instance = ();
+
+            fn register(_: ()) -> Register {
+                Register::$register
+            }
+
+            fn from_raw(raw: u8) -> Self {
+                Self(raw & Self::FIELDS_MASK)
+            }
+        }
+
+        impl WritableRegister for $name {
+            fn into_raw(self) -> u8 {
+                self.0
+            }
+        }
+    };
+}
+
+/// Defines a register holding one typed bit per pin of a port, along with its reset value.
+///
+/// Registers marked `@read_only` do not implement [`WritableRegister`].
+macro_rules! port_register {
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident<$ty:ty>($port0:ident, $port1:ident) = $reset:literal;
+    ) => {
+        port_register! {
+            @read_only
+            $(#[$attr])*
+            pub struct $name<$ty>($port0, $port1) = $reset;
+        }
+
+        impl WritableRegister for $name {
+            fn into_raw(self) -> u8 {
+                self.0
+            }
+        }
+    };
+    (
+        @read_only
+        $(#[$attr:meta])*
+        pub struct $name:ident<$ty:ty>($port0:ident, $port1:ident) = $reset:literal;
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub struct $name(u8);
+
+        impl Default for $name {
+            /// Gets the content of the register after a reset.
+            fn default() -> Self {
+                Self($reset)
+            }
+        }
+
+        impl $name {
+            /// Creates the register with all the pins set to the same value.
+            pub fn all(value: $ty) -> Self {
+                Self(if Field::into_bits(value) != 0 { 0xFF } else { 0x00 })
+            }
+
+            /// Gets the value of a pin.
+            pub fn get(&self, pin: PortPin) -> $ty {
+                <$ty as Field>::from_bits((self.0 >> pin as u8) & 0x01)
+            }
+
+            /// Sets the value of a pin.
+            pub fn set(&mut self, pin: PortPin, value: $ty) {
+                let mask = 1 << pin as u8;
+                self.0 = (self.0 & !mask) | ((Field::into_bits(value) & 0x01) << pin as u8);
+            }
+        }
+
+        impl TypedRegister for $name {
+            type Instance = Port;
+
+            fn register(port: Port) -> Register {
+                match port {
+                    Port::Port0 => Register::$port0,
+                    Port::Port1 => Register::$port1,
+                }
+            }
+
+            fn from_raw(raw: u8) -> Self {
+                Self(raw)
+            }
+        }
+    };
+}
+
+/// Direction of a GPIO-mode pin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Output,
+    Input,
+}
+
+/// Interrupt setting of an input pin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interrupt {
+    Enabled,
+    Disabled,
+}
+
+/// Function of a pin, either a constant current LED driver or a GPIO.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LedMode {
+    Led,
+    Gpio,
+}
+
+impl Field for bool {
+    fn from_bits(bits: u8) -> Self {
+        bits != 0
+    }
+
+    fn into_bits(self) -> u8 {
+        self as u8
+    }
+}
+
+impl Field for PinState {
+    fn from_bits(bits: u8) -> Self {
+        (bits != 0).into()
+    }
+
+    fn into_bits(self) -> u8 {
+        match self {
+            PinState::Low => 0,
+            PinState::High => 1,
+        }
+    }
+}
+
+impl Field for Direction {
+    fn from_bits(bits: u8) -> Self {
+        match bits {
+            0 => Direction::Output,
+            _ => Direction::Input,
+        }
+    }
+
+    fn into_bits(self) -> u8 {
+        match self {
+            Direction::Output => 0,
+            Direction::Input => 1,
+        }
+    }
+}
+
+impl Field for Interrupt {
+    fn from_bits(bits: u8) -> Self {
+        match bits {
+            0 => Interrupt::Enabled,
+            _ => Interrupt::Disabled,
+        }
+    }
+
+    fn into_bits(self) -> u8 {
+        match self {
+            Interrupt::Enabled => 0,
+            Interrupt::Disabled => 1,
+        }
+    }
+}
+
+impl Field for LedMode {
+    fn from_bits(bits: u8) -> Self {
+        match bits {
+            0 => LedMode::Led,
+            _ => LedMode::Gpio,
+        }
+    }
+
+    fn into_bits(self) -> u8 {
+        match self {
+            LedMode::Led => 0,
+            LedMode::Gpio => 1,
+        }
+    }
+}
+
+impl Field for Port0OutputDriveMode {
+    fn from_bits(bits: u8) -> Self {
+        (bits != 0).into()
+    }
+
+    fn into_bits(self) -> u8 {
+        match self {
+            Port0OutputDriveMode::OpenDrain => 0,
+            Port0OutputDriveMode::PushPull => 1,
+        }
+    }
+}
+
+impl Field for DriveCurrent {
+    fn from_bits(bits: u8) -> Self {
+        match bits & 0x03 {
+            0 => DriveCurrent::Max,
+            1 => DriveCurrent::High,
+            2 => DriveCurrent::Mid,
+            _ => DriveCurrent::Low,
+        }
+    }
+
+    fn into_bits(self) -> u8 {
+        self as u8
+    }
+}
+
+port_register! {
+    @read_only
+    /// Levels on the pins of a port.
+    pub struct InputPort<PinState>(InputPort0, InputPort1) = 0x00;
+}
+
+port_register! {
+    /// Levels driven on the GPIO-mode output pins of a port.
+    pub struct OutputPort<PinState>(OutputPort0, OutputPort1) = 0x00;
+}
+
+port_register! {
+    /// Direction of the GPIO-mode pins of a port.
+    pub struct ConfigPort<Direction>(ConfigPort0, ConfigPort1) = 0x00;
+}
+
+port_register! {
+    /// Interrupt settings of the input pins of a port.
+    pub struct IntPort<Interrupt>(IntPort0, IntPort1) = 0x00;
+}
+
+port_register! {
+    /// Function of the pins of a port.
+    pub struct LedModeSwitch<LedMode>(LedModeSwitchP0, LedModeSwitchP1) = 0xFF;
+}
+
+bitfield! {
+    /// Global control register.
+    pub struct Ctl(Ctl) {
+        /// Output drive mode of the pins of port 0.
+        port0_drive_mode, set_port0_drive_mode: Port0OutputDriveMode = 4, 1;
+
+        /// Maximum current of the pins in LED mode.
+        drive_current, set_drive_current: DriveCurrent = 0, 2;
+    }
 }
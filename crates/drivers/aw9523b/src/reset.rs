@@ -0,0 +1,122 @@
+//! Hardware reset through the RSTN line.
+//!
+//! Driving RSTN low resets every register of the device, including the ones a software reset
+//! cannot reach when the I2C bus is stuck. The driver takes ownership of the line with
+//! [`Aw9523b::with_reset_pin`]:
+//!
+//! ```ignore
+//! let mut io_expander = Aw9523b::new(i2c, 0x5B).with_reset_pin(reset_gpio);
+//! io_expander.reset_and_configure(&mut Delay, &CONFIG).await?;
+//! ```
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::delay::DelayUs;
+
+use crate::config::Aw9523bConfig;
+use crate::recovery::Shadow;
+use crate::{Aw9523b, AwError, NoResetPin};
+
+/// Time RSTN is held low, the datasheet requiring at least 20 µs.
+const RESET_PULSE_US: u32 = 50;
+
+/// Time after RSTN is released before the device is accessed.
+///
+/// The datasheet does not specify it, this leaves the internal reset plenty of margin.
+const RESET_RECOVERY_US: u32 = 1000;
+
+/// The RSTN line as seen by the driver, either an output pin or [`NoResetPin`].
+pub trait ResetLine {
+    /// Whether the driver controls the line.
+    const IS_CONTROLLED: bool;
+
+    /// Drives the line low or releases it, doing nothing if the line is not controlled.
+    fn set_level<E>(&mut self, high: bool) -> Result<(), AwError<E>>;
+}
+
+impl<P: OutputPin> ResetLine for P {
+    const IS_CONTROLLED: bool = true;
+
+    fn set_level<E>(&mut self, high: bool) -> Result<(), AwError<E>> {
+        let result = if high { self.set_high() } else { self.set_low() };
+        result.map_err(|_| AwError::ResetPin)
+    }
+}
+
+impl ResetLine for NoResetPin {
+    const IS_CONTROLLED: bool = false;
+
+    fn set_level<E>(&mut self, _high: bool) -> Result<(), AwError<E>> {
+        Ok(())
+    }
+}
+
+impl<I2C, RST, E> Aw9523b<I2C, RST>
+where
+    I2C: embedded_hal_async::i2c::I2c + embedded_hal::i2c::ErrorType<Error = E>,
+    RST: ResetLine,
+{
+    /// Resets the device through RSTN if the driver controls it, with a software reset otherwise.
+    pub async fn reset<D: DelayUs>(&mut self, delay: &mut D) -> Result<(), AwError<E>> {
+        if RST::IS_CONTROLLED {
+            self.pulse_reset(delay).await
+        } else {
+            self.software_reset().await
+        }
+    }
+
+    async fn pulse_reset<D: DelayUs>(&mut self, delay: &mut D) -> Result<(), AwError<E>> {
+        self.reset_pin.set_level(false)?;
+        self.shadow = Shadow::reset();
+        delay.delay_us(RESET_PULSE_US).await;
+
+        self.reset_pin.set_level(true)?;
+        delay.delay_us(RESET_RECOVERY_US).await;
+        Ok(())
+    }
+}
+
+impl<I2C, RST, E> Aw9523b<I2C, RST>
+where
+    I2C: embedded_hal_async::i2c::I2c + embedded_hal::i2c::ErrorType<Error = E>,
+    RST: OutputPin,
+{
+    /// Pulses RSTN, bringing every register back to its default value.
+    ///
+    /// The configuration written so far is forgotten, [`Aw9523b::restore`] has nothing to write
+    /// back afterwards.
+    pub async fn hardware_reset<D: DelayUs>(&mut self, delay: &mut D) -> Result<(), AwError<E>> {
+        self.pulse_reset(delay).await
+    }
+
+    /// Drives RSTN low and leaves it there, e.g. to save power while the device is unused.
+    ///
+    /// The device does not answer until the next [`Aw9523b::hardware_reset`].
+    pub fn hold_in_reset(&mut self) -> Result<(), AwError<E>> {
+        self.reset_pin.set_low().map_err(|_| AwError::ResetPin)?;
+        self.shadow = Shadow::reset();
+        Ok(())
+    }
+
+    /// Resets the device through RSTN, checks that it answers and writes a configuration to it.
+    pub async fn reset_and_configure<D: DelayUs>(
+        &mut self,
+        delay: &mut D,
+        config: &Aw9523bConfig,
+    ) -> Result<(), AwError<E>> {
+        self.hardware_reset(delay).await?;
+        self.probe().await?;
+        self.configure(config).await
+    }
+
+    /// Gives the RSTN line back, e.g. to drive it from somewhere else.
+    pub fn release_reset_pin(self) -> (Aw9523b<I2C>, RST) {
+        let driver = Aw9523b {
+            i2c: self.i2c,
+            addr: self.addr,
+            shadow: self.shadow,
+            reset_pin: NoResetPin,
+            retry_policy: self.retry_policy,
+        };
+        (driver, self.reset_pin)
+    }
+}
@@ -0,0 +1,73 @@
+use embedded_hal::i2c::{Error, ErrorKind, ErrorType};
+
+/// Bus errors retried by the driver before giving up, and how many times.
+///
+/// Only transient errors can be retried: a NACK while the device is busy or just out of reset, or
+/// an arbitration loss on a bus shared with another controller. Every access is retried as a
+/// whole, which is harmless since rewriting or rereading a register has no other side effect than
+/// clearing the interrupt of an input port again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RetryPolicy {
+    max_attempts: u8,
+    retry_nack: bool,
+    retry_arbitration_loss: bool,
+}
+
+impl RetryPolicy {
+    /// Every error is reported right away, the default.
+    pub const NEVER: Self = Self {
+        max_attempts: 1,
+        retry_nack: false,
+        retry_arbitration_loss: false,
+    };
+
+    /// Retries NACKs and arbitration losses until an access was attempted `max_attempts` times.
+    pub const fn new(max_attempts: u8) -> Self {
+        Self {
+            max_attempts,
+            retry_nack: true,
+            retry_arbitration_loss: true,
+        }
+    }
+
+    /// Sets whether NACKs are retried.
+    pub const fn with_nack(mut self, retry: bool) -> Self {
+        self.retry_nack = retry;
+        self
+    }
+
+    /// Sets whether arbitration losses are retried.
+    pub const fn with_arbitration_loss(mut self, retry: bool) -> Self {
+        self.retry_arbitration_loss = retry;
+        self
+    }
+
+    pub const fn max_attempts(&self) -> u8 {
+        self.max_attempts
+    }
+
+    /// Checks if an access that failed after `attempts` attempts must be tried again.
+    pub fn should_retry(&self, error: ErrorKind, attempts: u8) -> bool {
+        if attempts >= self.max_attempts {
+            return false;
+        }
+
+        match error {
+            ErrorKind::NoAcknowledge(_) => self.retry_nack,
+            ErrorKind::ArbitrationLoss => self.retry_arbitration_loss,
+            _ => false,
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::NEVER
+    }
+}
+
+/// Gets the kind of an error of a bus, whose error type is otherwise only known to be `E`.
+pub(crate) fn error_kind<I2C: ErrorType>(_i2c: &I2C, error: &I2C::Error) -> ErrorKind {
+    error.kind()
+}
@@ -0,0 +1,385 @@
+//! Register-level simulation of an AW9523B device.
+//!
+//! [`Simulator`] implements [`embedded_hal_async::i2c::I2c`] and models the register file of the
+//! device, so the driver and the code built on top of it can be exercised on the host. Cloning a
+//! [`Simulator`] returns another handle to the same device, which allows inspecting the register
+//! file and the transaction log after the driver took ownership of the bus.
+//!
+//! The blocking [`embedded_hal::i2c::I2c`] trait is implemented as well, for use with
+//! [`crate::blocking::Blocking`].
+extern crate std;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::vec::Vec;
+
+use embedded_hal::i2c::{ErrorKind, ErrorType, NoAcknowledgeSource, Operation};
+
+use crate::register::Register;
+
+pub use crate::recovery::DEVICE_ID;
+
+/// Number of addressable registers, `0x00..=0x7F`.
+const REGISTER_COUNT: usize = 0x80;
+
+/// A single I2C operation captured by the simulator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op {
+    /// Bytes written by the controller, starting with the register address.
+    Write(Vec<u8>),
+
+    /// Bytes returned to the controller.
+    Read(Vec<u8>),
+}
+
+/// A complete I2C transaction captured by the simulator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    pub address: u8,
+    pub ops: Vec<Op>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimError {
+    /// The transaction was addressed to a different device.
+    NoAcknowledge,
+
+    /// Another controller won the bus, only raised by [`Simulator::fail_next`].
+    ArbitrationLoss,
+}
+
+impl embedded_hal::i2c::Error for SimError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            SimError::NoAcknowledge => ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address),
+            SimError::ArbitrationLoss => ErrorKind::ArbitrationLoss,
+        }
+    }
+}
+
+struct Device {
+    address: u8,
+    registers: [u8; REGISTER_COUNT],
+    pointer: u8,
+    input_levels: [u8; 2],
+    output_port0_reset_value: u8,
+    last_read_inputs: [u8; 2],
+    /// Closed switches between two pins, as pin indices `0..16`.
+    switches: Vec<(usize, usize)>,
+    /// Errors returned by the next transactions, which are then ignored by the device.
+    injected_errors: Vec<SimError>,
+    log: Vec<Transaction>,
+}
+
+impl Device {
+    fn reset(&mut self) {
+        self.registers = [0x00; REGISTER_COUNT];
+        self.registers[Register::OutputPort0.addr() as usize] = self.output_port0_reset_value;
+        self.registers[Register::Id.addr() as usize] = DEVICE_ID;
+        self.registers[Register::LedModeSwitchP0.addr() as usize] = 0xFF;
+        self.registers[Register::LedModeSwitchP1.addr() as usize] = 0xFF;
+        self.pointer = 0x00;
+        self.last_read_inputs = [self.input_port(0), self.input_port(1)];
+    }
+
+    /// Level seen on the pins of a port: external levels for inputs, driven levels for outputs.
+    ///
+    /// Inputs joined to outputs by closed switches follow them instead, low winning over high.
+    fn input_port(&self, port: usize) -> u8 {
+        let levels = self.pin_levels();
+        (levels >> (port * 8)) as u8
+    }
+
+    fn pin_levels(&self) -> u16 {
+        let port_pair = |register: Register| {
+            let addr = register.addr() as usize;
+            u16::from_le_bytes([self.registers[addr], self.registers[addr + 1]])
+        };
+        let config = port_pair(Register::ConfigPort0);
+        let output = port_pair(Register::OutputPort0);
+        let external = u16::from_le_bytes(self.input_levels);
+
+        // Pins joined by closed switches form nets, labelled by their lowest pin
+        let mut nets: [usize; 16] = core::array::from_fn(|pin| pin);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &(a, b) in &self.switches {
+                let net = nets[a].min(nets[b]);
+                if nets[a] != net || nets[b] != net {
+                    nets[a] = net;
+                    nets[b] = net;
+                    changed = true;
+                }
+            }
+        }
+
+        let mut levels = 0;
+        for pin in 0..16 {
+            let bit = 1 << pin;
+            let level = if config & bit == 0 {
+                output & bit != 0
+            } else {
+                let members = (0..16).filter(|&other| nets[other] == nets[pin]);
+                let driven: Vec<_> = members.clone().filter(|&other| config & (1 << other) == 0).collect();
+                if driven.is_empty() {
+                    members.into_iter().all(|other| external & (1 << other) != 0)
+                } else {
+                    driven.into_iter().all(|other| output & (1 << other) != 0)
+                }
+            };
+            if level {
+                levels |= bit;
+            }
+        }
+        levels
+    }
+
+    fn interrupt_pending(&self, port: usize) -> bool {
+        let config = self.registers[Register::ConfigPort0.addr() as usize + port];
+        // A cleared bit in the interrupt register enables the interrupt
+        let enabled = !self.registers[Register::IntPort0.addr() as usize + port];
+        let changed = self.input_port(port) ^ self.last_read_inputs[port];
+        (changed & config & enabled) != 0
+    }
+
+    fn read_byte(&mut self) -> u8 {
+        let addr = self.pointer;
+        self.pointer = addr.wrapping_add(1) & 0x7F;
+
+        let Some(register) = Register::from_addr(addr) else {
+            return 0x00;
+        };
+
+        match register {
+            Register::InputPort0 | Register::InputPort1 => {
+                let port = addr as usize;
+                let value = self.input_port(port);
+                // Reading the input port clears the interrupt of that port
+                self.last_read_inputs[port] = value;
+                value
+            }
+            r if r.is_write_only() => 0x00,
+            r => self.registers[r.addr() as usize],
+        }
+    }
+
+    fn write_byte(&mut self, value: u8) {
+        let addr = self.pointer;
+        self.pointer = addr.wrapping_add(1) & 0x7F;
+
+        let Some(register) = Register::from_addr(addr) else {
+            return;
+        };
+
+        match register {
+            r if r.is_read_only() => {}
+            Register::SwRstn => {
+                if value == 0x00 {
+                    self.reset();
+                }
+            }
+            r => self.registers[r.addr() as usize] = value,
+        }
+    }
+}
+
+fn pin_index(pin: crate::Pin) -> usize {
+    pin.0 as usize * 8 + pin.1 as usize
+}
+
+/// Simulated AW9523B attached to an I2C bus.
+#[derive(Clone)]
+pub struct Simulator {
+    device: Rc<RefCell<Device>>,
+}
+
+impl Simulator {
+    /// Creates a simulated device answering to the given address, in its power-on state.
+    pub fn new(address: u8) -> Self {
+        let mut device = Device {
+            address,
+            registers: [0x00; REGISTER_COUNT],
+            pointer: 0x00,
+            input_levels: [0xFF; 2],
+            output_port0_reset_value: 0x00,
+            last_read_inputs: [0x00; 2],
+            switches: Vec::new(),
+            injected_errors: Vec::new(),
+            log: Vec::new(),
+        };
+        device.reset();
+
+        Self {
+            device: Rc::new(RefCell::new(device)),
+        }
+    }
+
+    /// Sets the value `OutputPort0` takes after a reset.
+    ///
+    /// On the real device it depends on the strapping of the AD0/AD1 pins.
+    pub fn with_output_port0_reset_value(self, value: u8) -> Self {
+        {
+            let mut device = self.device.borrow_mut();
+            device.output_port0_reset_value = value;
+            device.reset();
+        }
+        self
+    }
+
+    /// Gets the raw content of a register, without side effects.
+    pub fn register(&self, register: Register) -> u8 {
+        let device = self.device.borrow();
+        match register {
+            Register::InputPort0 => device.input_port(0),
+            Register::InputPort1 => device.input_port(1),
+            r => device.registers[r.addr() as usize],
+        }
+    }
+
+    /// Overwrites the raw content of a register, bypassing the bus.
+    ///
+    /// For the input ports this sets the external levels applied to the pins.
+    pub fn set_register(&self, register: Register, value: u8) {
+        let mut device = self.device.borrow_mut();
+        match register {
+            Register::InputPort0 => device.input_levels[0] = value,
+            Register::InputPort1 => device.input_levels[1] = value,
+            r => device.registers[r.addr() as usize] = value,
+        }
+    }
+
+    /// Sets the external levels applied to the pins of a port.
+    pub fn set_input_levels(&self, port: crate::Port, levels: u8) {
+        self.device.borrow_mut().input_levels[port as usize] = levels;
+    }
+
+    /// Closes a switch between two pins, e.g. a key of a matrix.
+    pub fn close_switch(&self, a: crate::Pin, b: crate::Pin) {
+        let switch = (pin_index(a), pin_index(b));
+        self.device.borrow_mut().switches.push(switch);
+    }
+
+    /// Opens a switch closed by [`Simulator::close_switch`].
+    pub fn open_switch(&self, a: crate::Pin, b: crate::Pin) {
+        let (a, b) = (pin_index(a), pin_index(b));
+        self.device
+            .borrow_mut()
+            .switches
+            .retain(|&switch| switch != (a, b) && switch != (b, a));
+    }
+
+    /// Returns `true` while the INTN line is asserted.
+    pub fn is_interrupt_asserted(&self) -> bool {
+        let device = self.device.borrow();
+        device.interrupt_pending(0) || device.interrupt_pending(1)
+    }
+
+    /// Simulates a power cycle of the device.
+    pub fn power_cycle(&self) {
+        self.device.borrow_mut().reset();
+    }
+
+    /// Makes the next `count` transactions fail with an error, without reaching the device.
+    pub fn fail_next(&self, count: usize, error: SimError) {
+        let mut device = self.device.borrow_mut();
+        for _ in 0..count {
+            device.injected_errors.push(error);
+        }
+    }
+
+    /// Gets a copy of all the transactions seen so far.
+    pub fn transactions(&self) -> Vec<Transaction> {
+        self.device.borrow().log.clone()
+    }
+
+    /// Clears the transaction log.
+    pub fn clear_transactions(&self) {
+        self.device.borrow_mut().log.clear();
+    }
+
+    fn execute(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), SimError> {
+        let mut device = self.device.borrow_mut();
+        let mut ops = Vec::with_capacity(operations.len());
+
+        if address != device.address {
+            device.log.push(Transaction { address, ops });
+            return Err(SimError::NoAcknowledge);
+        }
+
+        if !device.injected_errors.is_empty() {
+            device.log.push(Transaction { address, ops });
+            return Err(device.injected_errors.remove(0));
+        }
+
+        // Adjacent writes are sent without a repeated start, so only the first byte after a start
+        // condition sets the register pointer, the rest are data
+        let mut expect_pointer = true;
+
+        for operation in operations.iter_mut() {
+            match operation {
+                Operation::Write(bytes) => {
+                    for &value in bytes.iter() {
+                        if expect_pointer {
+                            device.pointer = value & 0x7F;
+                            expect_pointer = false;
+                        } else {
+                            device.write_byte(value);
+                        }
+                    }
+                    ops.push(Op::Write(bytes.to_vec()));
+                }
+                Operation::Read(buffer) => {
+                    expect_pointer = true;
+                    for byte in buffer.iter_mut() {
+                        *byte = device.read_byte();
+                    }
+                    ops.push(Op::Read(buffer.to_vec()));
+                }
+            }
+        }
+
+        device.log.push(Transaction { address, ops });
+        Ok(())
+    }
+}
+
+impl ErrorType for Simulator {
+    type Error = SimError;
+}
+
+impl embedded_hal_async::i2c::I2c for Simulator {
+    async fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.execute(address, &mut [Operation::Read(read)])
+    }
+
+    async fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        self.execute(address, &mut [Operation::Write(write)])
+    }
+
+    async fn write_read(&mut self, address: u8, write: &[u8], read: &mut [u8]) -> Result<(), Self::Error> {
+        self.execute(address, &mut [Operation::Write(write), Operation::Read(read)])
+    }
+
+    async fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), Self::Error> {
+        self.execute(address, operations)
+    }
+}
+
+impl embedded_hal::i2c::I2c for Simulator {
+    fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.execute(address, &mut [Operation::Read(read)])
+    }
+
+    fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        self.execute(address, &mut [Operation::Write(write)])
+    }
+
+    fn write_read(&mut self, address: u8, write: &[u8], read: &mut [u8]) -> Result<(), Self::Error> {
+        self.execute(address, &mut [Operation::Write(write), Operation::Read(read)])
+    }
+
+    fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), Self::Error> {
+        self.execute(address, operations)
+    }
+}
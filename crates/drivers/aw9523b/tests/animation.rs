@@ -0,0 +1,126 @@
+use aw9523b::animation::{Animation, Animator, Frame, NoFreeSlot, Step, Target};
+use aw9523b::sim::Simulator;
+use aw9523b::{Aw9523b, Pin, Port, PortPin, Register};
+use embassy_futures::block_on;
+
+const ADDR: u8 = 0x5B;
+
+const LED: Pin = Pin(Port::Port0, PortPin::P0);
+const RGB: [Pin; 3] = [
+    Pin(Port::Port1, PortPin::P0),
+    Pin(Port::Port1, PortPin::P2),
+    Pin(Port::Port1, PortPin::P1),
+];
+
+#[test]
+fn fade_is_one_shot() {
+    let mut animator = Animator::<1>::new();
+    let fade = Animation::Fade { from: 0, to: 200, duration: 100 };
+    animator.start(Target::Pin(LED), fade, 1000).unwrap();
+
+    assert_eq!(animator.tick(1000).level(LED), 0);
+    assert_eq!(animator.tick(1050).level(LED), 100);
+    assert!(animator.is_animating(LED));
+    assert_eq!(animator.tick(1100).level(LED), 200);
+    assert!(!animator.is_animating(LED));
+    assert_eq!(animator.tick(5000).level(LED), 200);
+}
+
+#[test]
+fn blink_and_breathe() {
+    let mut animator = Animator::<2>::new();
+    let other = Pin(Port::Port0, PortPin::P1);
+    animator.start(Target::Pin(LED), Animation::Blink { level: 80, on: 10, off: 30 }, 0).unwrap();
+    animator.start(Target::Pin(other), Animation::Breathe { min: 0, max: 100, period: 200 }, 0).unwrap();
+
+    let levels: Vec<_> = [0, 9, 10, 39, 40, 100]
+        .into_iter()
+        .map(|t| {
+            let frame = animator.tick(t);
+            (frame.level(LED), frame.level(other))
+        })
+        .collect();
+
+    assert_eq!(levels, [(80, 0), (80, 9), (0, 10), (0, 39), (80, 40), (0, 100)]);
+}
+
+#[test]
+fn color_cycle_on_rgb() {
+    const COLORS: [[u8; 3]; 2] = [[255, 0, 0], [0, 0, 255]];
+
+    let mut animator = Animator::<1>::new();
+    let cycle = Animation::ColorCycle { colors: &COLORS, transition: 100 };
+    animator.start(Target::Rgb(RGB, [255, 255, 255]), cycle, 0).unwrap();
+
+    let frame = animator.tick(50);
+    assert_eq!([frame.level(RGB[0]), frame.level(RGB[1]), frame.level(RGB[2])], [128, 0, 127]);
+
+    let frame = animator.tick(100);
+    assert_eq!([frame.level(RGB[0]), frame.level(RGB[1]), frame.level(RGB[2])], [0, 0, 255]);
+}
+
+#[test]
+fn monochrome_animation_scaled_by_color() {
+    let mut animator = Animator::<1>::new();
+    animator.start(Target::Rgb(RGB, [255, 128, 0]), Animation::Blink { level: 200, on: 10, off: 10 }, 0).unwrap();
+
+    let frame = animator.tick(0);
+    assert_eq!([frame.level(RGB[0]), frame.level(RGB[1]), frame.level(RGB[2])], [200, 100, 0]);
+}
+
+#[test]
+fn sequence() {
+    const STEPS: [Step; 3] = [Step::Ramp(100, 100), Step::Hold(100, 50), Step::Ramp(0, 50)];
+
+    let mut animator = Animator::<1>::new();
+    animator.start(Target::Pin(LED), Animation::Sequence { steps: &STEPS, repeat: false }, 0).unwrap();
+
+    let levels: Vec<_> = [0, 50, 120, 175, 199, 200].into_iter().map(|t| animator.tick(t).level(LED)).collect();
+    assert_eq!(levels, [0, 50, 100, 50, 2, 0]);
+    assert!(!animator.is_animating(LED));
+}
+
+#[test]
+fn starting_on_a_busy_pin_replaces_the_animation() {
+    let mut animator = Animator::<2>::new();
+    animator.start(Target::Rgb(RGB, [255; 3]), Animation::Blink { level: 255, on: 10, off: 10 }, 0).unwrap();
+    animator.start(Target::Pin(LED), Animation::Blink { level: 255, on: 10, off: 10 }, 0).unwrap();
+
+    let fade = Animation::Fade { from: 0, to: 10, duration: 10 };
+    assert_eq!(animator.start(Target::Pin(Pin(Port::Port0, PortPin::P7)), fade, 0), Err(NoFreeSlot));
+
+    animator.start(Target::Pin(RGB[1]), fade, 0).unwrap();
+    assert!(!animator.is_animating(RGB[0]));
+    assert!(animator.is_animating(RGB[1]));
+    assert!(animator.is_animating(LED));
+}
+
+#[test]
+fn frame_writes_are_batched() {
+    let sim = Simulator::new(ADDR);
+    let mut driver = Aw9523b::new(sim.clone(), ADDR);
+
+    // P1.0, P1.1 and P1.2 map to DIM0..DIM2, P0.0 maps to DIM4 and P1.7 maps to DIM15
+    let mut frame = Frame::new();
+    frame.set(RGB[0], 10);
+    frame.set(RGB[1], 20);
+    frame.set(RGB[2], 30);
+    frame.set(LED, 40);
+    frame.set(Pin(Port::Port1, PortPin::P7), 50);
+
+    block_on(driver.write_led_frame(&mut frame)).unwrap();
+
+    assert_eq!(sim.transactions().len(), 2);
+    assert_eq!(sim.register(Register::Dim0), 10);
+    assert_eq!(sim.register(Register::Dim1), 30);
+    assert_eq!(sim.register(Register::Dim2), 20);
+    assert_eq!(sim.register(Register::Dim3), 0);
+    assert_eq!(sim.register(Register::Dim4), 40);
+    assert_eq!(sim.register(Register::Dim15), 50);
+    assert!(!frame.is_dirty());
+
+    // Nothing changed, nothing to write
+    sim.clear_transactions();
+    block_on(driver.write_led_frame(&mut frame)).unwrap();
+    assert!(sim.transactions().is_empty());
+}
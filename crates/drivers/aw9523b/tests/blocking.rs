@@ -0,0 +1,21 @@
+use aw9523b::blocking::block_on;
+use aw9523b::sim::{Simulator, DEVICE_ID};
+use aw9523b::{Aw9523b, Pin, PinMode, PinState, Port, PortPin, Register};
+
+const ADDR: u8 = 0x5B;
+
+#[test]
+fn driver_on_blocking_bus() {
+    let sim = Simulator::new(ADDR);
+    let mut driver = Aw9523b::new_blocking(sim.clone(), ADDR);
+    let pin = Pin(Port::Port1, PortPin::P3);
+
+    assert_eq!(block_on(driver.read_device_id()).unwrap(), DEVICE_ID);
+
+    block_on(driver.set_pin_config(pin, PinMode::Output)).unwrap();
+    block_on(driver.set_pin_output_state(pin, PinState::High)).unwrap();
+
+    assert_eq!(sim.register(Register::ConfigPort1), 0x00);
+    assert_eq!(sim.register(Register::OutputPort1), 0x08);
+    assert_eq!(block_on(driver.read_pin(pin)).unwrap(), PinState::High);
+}
@@ -0,0 +1,76 @@
+use aw9523b::animation::Frame;
+use aw9523b::brightness::{Brightness, Curve};
+use aw9523b::sim::Simulator;
+use aw9523b::{Aw9523b, Pin, Port, PortPin, Register};
+use embassy_futures::block_on;
+
+const ADDR: u8 = 0x5B;
+
+const LED_R: Pin = Pin(Port::Port1, PortPin::P0);
+const LED_G: Pin = Pin(Port::Port1, PortPin::P2);
+
+#[test]
+fn curves_are_monotonic_and_span_full_range() {
+    const TABLE: [u8; 256] = {
+        let mut table = [0u8; 256];
+        let mut i = 0;
+        while i < 256 {
+            table[i] = (255 - i) as u8;
+            i += 1;
+        }
+        table
+    };
+
+    for curve in [Curve::Linear, Curve::Gamma22, Curve::Cie1931] {
+        assert_eq!(curve.apply(0), 0);
+        assert_eq!(curve.apply(255), 255);
+
+        for level in 1..=255u8 {
+            assert!(curve.apply(level) >= curve.apply(level - 1), "{curve:?} at {level}");
+        }
+    }
+
+    assert_eq!(Curve::Custom(&TABLE).apply(10), 245);
+}
+
+#[test]
+fn curves_match_reference_values() {
+    // Reference values from the floating point definitions
+    let cie = [(10, 1), (21, 2), (50, 7), (128, 47), (200, 138), (254, 252)];
+    for (level, expected) in cie {
+        assert_eq!(Curve::Cie1931.apply(level), expected);
+    }
+
+    let gamma = [(15, 1), (64, 12), (128, 56), (200, 149)];
+    for (level, expected) in gamma {
+        assert_eq!(Curve::Gamma22.apply(level), expected);
+    }
+}
+
+#[test]
+fn global_level_and_white_balance() {
+    const BRIGHTNESS: Brightness = Brightness::new(Curve::Linear).with_level(128).with_balance(LED_G, 191);
+
+    assert_eq!(BRIGHTNESS.apply(LED_R, 255), 128);
+    assert_eq!(BRIGHTNESS.apply(LED_G, 255), 96);
+    assert_eq!(BRIGHTNESS.apply(LED_R, 0), 0);
+    assert_eq!(BRIGHTNESS.balance(LED_R), 255);
+}
+
+#[test]
+fn frame_writes_corrected_levels() {
+    let sim = Simulator::new(ADDR);
+    let mut driver = Aw9523b::new(sim.clone(), ADDR);
+
+    let mut frame = Frame::with_brightness(Brightness::new(Curve::Cie1931));
+    frame.set(LED_R, 128);
+    block_on(driver.write_led_frame(&mut frame)).unwrap();
+    assert_eq!(sim.register(Register::Dim0), 47);
+
+    // Changing the brightness rewrites every level
+    sim.clear_transactions();
+    frame.set_brightness(Brightness::new(Curve::Linear).with_level(0));
+    block_on(driver.write_led_frame(&mut frame)).unwrap();
+    assert_eq!(sim.register(Register::Dim0), 0);
+    assert_eq!(sim.transactions().len(), 1);
+}
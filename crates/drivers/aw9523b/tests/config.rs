@@ -0,0 +1,108 @@
+use aw9523b::config::{Aw9523bConfig, RegisterDiff};
+use aw9523b::sim::{Op, Simulator};
+use aw9523b::{dim_register, Aw9523b, DriveCurrent, Pin, PinMode, PinState, Port, Port0OutputDriveMode, PortPin, Register};
+use embassy_futures::block_on;
+
+const ADDR: u8 = 0x5B;
+
+const BUTTON: Pin = Pin(Port::Port0, PortPin::P2);
+const ENABLE: Pin = Pin(Port::Port0, PortPin::P7);
+const LED: Pin = Pin(Port::Port1, PortPin::P0);
+
+const CONFIG: Aw9523bConfig = Aw9523bConfig::new()
+    .with_pin_mode(BUTTON, PinMode::Input)
+    .with_interrupt(BUTTON, true)
+    .with_pin_mode(LED, PinMode::Led)
+    .with_dim_level(LED, 0x40)
+    .with_drive_current(DriveCurrent::Mid);
+
+fn setup() -> (Simulator, Aw9523b<Simulator>) {
+    let sim = Simulator::new(ADDR);
+    let driver = Aw9523b::new(sim.clone(), ADDR);
+    (sim, driver)
+}
+
+#[test]
+fn const_builder() {
+    const FULL: Aw9523bConfig = CONFIG
+        .with_pin_mode(ENABLE, PinMode::Output)
+        .with_output(ENABLE, PinState::High)
+        .with_interrupt(ENABLE, false)
+        .with_port0_drive_mode(Port0OutputDriveMode::PushPull);
+
+    assert_eq!(FULL.pin_mode(BUTTON), PinMode::Input);
+    assert_eq!(FULL.pin_mode(LED), PinMode::Led);
+    assert_eq!(FULL.pin_mode(ENABLE), PinMode::Output);
+    assert!(FULL.interrupt(BUTTON));
+    assert!(!FULL.interrupt(ENABLE));
+    assert_eq!(FULL.output(ENABLE), PinState::High);
+    assert_eq!(FULL.output(BUTTON), PinState::Low);
+    assert_eq!(FULL.port0_drive_mode(), Port0OutputDriveMode::PushPull);
+    assert_eq!(FULL.drive_current(), DriveCurrent::Mid);
+    assert_eq!(FULL.dim_level(LED), 0x40);
+}
+
+#[test]
+fn configure_unknown_device_writes_everything() {
+    let (sim, mut driver) = setup();
+    sim.set_register(Register::ConfigPort1, 0xFF);
+    sim.set_register(Register::Ctl, 0x13);
+
+    block_on(driver.configure(&CONFIG)).unwrap();
+
+    assert_eq!(sim.register(Register::ConfigPort0), 0x04);
+    assert_eq!(sim.register(Register::ConfigPort1), 0x00);
+    assert_eq!(sim.register(Register::Ctl), 0x02);
+    assert_eq!(sim.register(Register::LedModeSwitchP1), 0xFE);
+    assert_eq!(sim.register(dim_register(LED)), 0x40);
+
+    // One burst per block of configuration registers
+    assert_eq!(sim.transactions().len(), 3);
+}
+
+#[test]
+fn configure_after_reset_skips_defaults() {
+    let (sim, mut driver) = setup();
+    block_on(driver.software_reset()).unwrap();
+    sim.clear_transactions();
+
+    block_on(driver.configure(&CONFIG)).unwrap();
+
+    // Port 0 outputs depend on the straps, then only the registers differing from their default
+    let writes: Vec<_> = sim.transactions().into_iter().map(|t| t.ops).collect();
+    assert_eq!(
+        writes,
+        [
+            vec![Op::Write(vec![0x02]), Op::Write(vec![0x00, 0x00, 0x04])],
+            vec![Op::Write(vec![0x20]), Op::Write(vec![0x40])],
+            vec![Op::Write(vec![0x11]), Op::Write(vec![0x02, 0xFF, 0xFE])],
+        ]
+    );
+
+    // Nothing left to write once configured
+    sim.clear_transactions();
+    block_on(driver.configure(&CONFIG)).unwrap();
+    assert!(sim.transactions().is_empty());
+}
+
+#[test]
+fn read_back_and_diff() {
+    let (sim, mut driver) = setup();
+    block_on(driver.configure(&CONFIG)).unwrap();
+
+    let actual = block_on(driver.read_config()).unwrap();
+    assert_eq!(actual, CONFIG);
+    assert!(block_on(driver.verify_config(&CONFIG)).unwrap().is_empty());
+
+    sim.set_register(Register::IntPort0, 0xFF);
+    let diff = block_on(driver.verify_config(&CONFIG)).unwrap();
+    let diffs: Vec<_> = diff.iter().collect();
+    assert_eq!(
+        diffs,
+        [RegisterDiff {
+            register: Register::IntPort0,
+            expected: 0x00,
+            actual: 0xFF,
+        }]
+    );
+}
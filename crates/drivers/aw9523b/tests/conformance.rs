@@ -0,0 +1,279 @@
+//! Checks the bit semantics of every getter/setter pair of the driver against the datasheet.
+
+use aw9523b::sim::Simulator;
+use aw9523b::{Aw9523b, DriveCurrent, Pin, PinMode, PinState, Port, Port0OutputDriveMode, PortPin, Register};
+use embassy_futures::block_on;
+
+const ADDR: u8 = 0x5B;
+
+const PORTS: [Port; 2] = [Port::Port0, Port::Port1];
+
+const PORT_PINS: [PortPin; 8] = [
+    PortPin::P0,
+    PortPin::P1,
+    PortPin::P2,
+    PortPin::P3,
+    PortPin::P4,
+    PortPin::P5,
+    PortPin::P6,
+    PortPin::P7,
+];
+
+fn setup() -> (Simulator, Aw9523b<Simulator>) {
+    let sim = Simulator::new(ADDR);
+    let driver = Aw9523b::new(sim.clone(), ADDR);
+    (sim, driver)
+}
+
+fn all_pins() -> impl Iterator<Item = Pin> {
+    PORTS
+        .into_iter()
+        .flat_map(|port| PORT_PINS.into_iter().map(move |pin| Pin(port, pin)))
+}
+
+fn bit(pin: Pin) -> u8 {
+    1 << pin.1 as u8
+}
+
+fn register(pin: Pin, port0: Register, port1: Register) -> Register {
+    match pin.0 {
+        Port::Port0 => port0,
+        Port::Port1 => port1,
+    }
+}
+
+#[test]
+fn pin_state_from_bool() {
+    assert_eq!(PinState::from(true), PinState::High);
+    assert_eq!(PinState::from(false), PinState::Low);
+}
+
+#[test]
+fn port0_drive_mode_from_bool() {
+    // CTL.GPOMD: 0 = open-drain, 1 = push-pull
+    assert_eq!(Port0OutputDriveMode::from(true), Port0OutputDriveMode::PushPull);
+    assert_eq!(Port0OutputDriveMode::from(false), Port0OutputDriveMode::OpenDrain);
+}
+
+#[test]
+fn read_pin_follows_input_level() {
+    let (sim, mut driver) = setup();
+
+    for port in PORTS {
+        block_on(driver.set_port_config(port, 0xFF)).unwrap();
+    }
+
+    for pin in all_pins() {
+        sim.set_input_levels(pin.0, bit(pin));
+        assert_eq!(block_on(driver.read_pin(pin)).unwrap(), PinState::High);
+        assert_eq!(block_on(driver.read_port(pin.0)).unwrap(), bit(pin));
+
+        sim.set_input_levels(pin.0, !bit(pin));
+        assert_eq!(block_on(driver.read_pin(pin)).unwrap(), PinState::Low);
+        assert_eq!(block_on(driver.read_port(pin.0)).unwrap(), !bit(pin));
+    }
+}
+
+#[test]
+fn port_output_state_round_trip() {
+    let (sim, mut driver) = setup();
+
+    for (port, register) in [(Port::Port0, Register::OutputPort0), (Port::Port1, Register::OutputPort1)] {
+        for value in [0x00, 0x5A, 0xA5, 0xFF] {
+            block_on(driver.set_port_output_state(port, value)).unwrap();
+            assert_eq!(sim.register(register), value);
+            assert_eq!(block_on(driver.get_port_output_state(port)).unwrap(), value);
+        }
+    }
+}
+
+#[test]
+fn pin_output_state_round_trip() {
+    let (sim, mut driver) = setup();
+
+    for pin in all_pins() {
+        let register = register(pin, Register::OutputPort0, Register::OutputPort1);
+        sim.set_register(register, 0x00);
+
+        block_on(driver.set_pin_output_state(pin, PinState::High)).unwrap();
+        assert_eq!(sim.register(register), bit(pin));
+        assert_eq!(block_on(driver.get_pin_output_state(pin)).unwrap(), PinState::High);
+
+        sim.set_register(register, 0xFF);
+
+        block_on(driver.set_pin_output_state(pin, PinState::Low)).unwrap();
+        assert_eq!(sim.register(register), !bit(pin));
+        assert_eq!(block_on(driver.get_pin_output_state(pin)).unwrap(), PinState::Low);
+    }
+}
+
+#[test]
+fn port_config_round_trip() {
+    let (sim, mut driver) = setup();
+
+    for (port, register) in [(Port::Port0, Register::ConfigPort0), (Port::Port1, Register::ConfigPort1)] {
+        for value in [0x00, 0x5A, 0xA5, 0xFF] {
+            block_on(driver.set_port_config(port, value)).unwrap();
+            assert_eq!(sim.register(register), value);
+            assert_eq!(block_on(driver.get_port_config(port)).unwrap(), value);
+        }
+    }
+}
+
+#[test]
+fn pin_config_round_trip() {
+    // Config: 0 = output, 1 = input. LED mode switch: 0 = LED, 1 = GPIO
+    let expected = [
+        (PinMode::Input, true, true),
+        (PinMode::Output, false, true),
+        (PinMode::Led, false, false),
+    ];
+
+    for (mode, config_bit, gpio_bit) in expected {
+        for background in [0x00, 0xFF] {
+            let (sim, mut driver) = setup();
+
+            for pin in all_pins() {
+                let config = register(pin, Register::ConfigPort0, Register::ConfigPort1);
+                let led_mode_switch = register(pin, Register::LedModeSwitchP0, Register::LedModeSwitchP1);
+                sim.set_register(config, background);
+                sim.set_register(led_mode_switch, background);
+
+                block_on(driver.set_pin_config(pin, mode)).unwrap();
+
+                let apply = |set: bool| if set { background | bit(pin) } else { background & !bit(pin) };
+                assert_eq!(sim.register(config), apply(config_bit));
+                assert_eq!(sim.register(led_mode_switch), apply(gpio_bit));
+                assert_eq!(block_on(driver.get_pin_config(pin)).unwrap(), mode);
+            }
+        }
+    }
+}
+
+#[test]
+fn led_pwm_maps_to_dim_registers() {
+    let (sim, mut driver) = setup();
+
+    // P1_0..P1_3 drive DIM0..DIM3, P0_0..P0_7 drive DIM4..DIM11 and P1_4..P1_7 drive DIM12..DIM15
+    let dim_addr = |pin: Pin| match pin {
+        Pin(Port::Port0, p) => 0x24 + p as u8,
+        Pin(Port::Port1, p) if (p as u8) < 4 => 0x20 + p as u8,
+        Pin(Port::Port1, p) => 0x28 + p as u8,
+    };
+
+    for (i, pin) in all_pins().enumerate() {
+        let pwm = 0x10 + i as u8;
+        block_on(driver.set_pin_led_pwm(pin, pwm)).unwrap();
+
+        let register = Register::from_addr(dim_addr(pin)).unwrap();
+        assert_eq!(sim.register(register), pwm);
+    }
+}
+
+#[test]
+fn port_interrupt_config_round_trip() {
+    let (sim, mut driver) = setup();
+
+    for (port, register) in [(Port::Port0, Register::IntPort0), (Port::Port1, Register::IntPort1)] {
+        for value in [0x00, 0x5A, 0xA5, 0xFF] {
+            block_on(driver.set_port_interrupt_config(port, value)).unwrap();
+            assert_eq!(sim.register(register), value);
+            assert_eq!(block_on(driver.get_port_interrupt_config(port)).unwrap(), value);
+        }
+    }
+}
+
+#[test]
+fn pin_interrupt_round_trip() {
+    let (sim, mut driver) = setup();
+
+    // Interrupt: 0 = enabled, 1 = disabled
+    for pin in all_pins() {
+        let register = register(pin, Register::IntPort0, Register::IntPort1);
+        sim.set_register(register, 0xFF);
+
+        block_on(driver.enable_pin_interrupt(pin, true)).unwrap();
+        assert_eq!(sim.register(register), !bit(pin));
+        assert!(block_on(driver.get_pin_interrupt_config(pin)).unwrap());
+
+        sim.set_register(register, 0x00);
+
+        block_on(driver.enable_pin_interrupt(pin, false)).unwrap();
+        assert_eq!(sim.register(register), bit(pin));
+        assert!(!block_on(driver.get_pin_interrupt_config(pin)).unwrap());
+    }
+}
+
+#[test]
+fn port0_drive_mode_round_trip() {
+    let (sim, mut driver) = setup();
+    sim.set_register(Register::Ctl, 0x03);
+
+    block_on(driver.set_port0_drive_mode(Port0OutputDriveMode::PushPull)).unwrap();
+    assert_eq!(sim.register(Register::Ctl), 0x13);
+    assert_eq!(block_on(driver.get_port0_drive_mode()).unwrap(), Port0OutputDriveMode::PushPull);
+
+    block_on(driver.set_port0_drive_mode(Port0OutputDriveMode::OpenDrain)).unwrap();
+    assert_eq!(sim.register(Register::Ctl), 0x03);
+    assert_eq!(block_on(driver.get_port0_drive_mode()).unwrap(), Port0OutputDriveMode::OpenDrain);
+}
+
+#[test]
+fn drive_current_round_trip() {
+    let (sim, mut driver) = setup();
+    sim.set_register(Register::Ctl, 0x10);
+
+    // CTL.ISEL: 00 = I_max, 01 = 3/4, 10 = 1/2, 11 = 1/4
+    let expected = [
+        (DriveCurrent::Max, 0x00),
+        (DriveCurrent::High, 0x01),
+        (DriveCurrent::Mid, 0x02),
+        (DriveCurrent::Low, 0x03),
+    ];
+
+    for (drive_current, bits) in expected {
+        block_on(driver.set_drive_current(drive_current)).unwrap();
+        assert_eq!(sim.register(Register::Ctl), 0x10 | bits);
+        assert_eq!(block_on(driver.get_drive_current()).unwrap(), drive_current);
+    }
+}
+
+#[test]
+fn port_led_mode_switch_round_trip() {
+    let (sim, mut driver) = setup();
+
+    for (port, register) in [(Port::Port0, Register::LedModeSwitchP0), (Port::Port1, Register::LedModeSwitchP1)] {
+        for value in [0x00, 0x5A, 0xA5, 0xFF] {
+            block_on(driver.set_port_led_mode_switch(port, value)).unwrap();
+            assert_eq!(sim.register(register), value);
+            assert_eq!(block_on(driver.get_port_led_mode_switch(port)).unwrap(), value);
+        }
+    }
+}
+
+#[test]
+fn ctl_reserved_bits_are_written_as_zero() {
+    let (sim, mut driver) = setup();
+    sim.set_register(Register::Ctl, 0xFF);
+
+    block_on(driver.set_drive_current(DriveCurrent::Mid)).unwrap();
+    assert_eq!(sim.register(Register::Ctl), 0x12);
+}
+
+#[test]
+fn typed_port_registers() {
+    use aw9523b::register::{ConfigPort, Direction, LedMode, LedModeSwitch, TypedRegister, WritableRegister};
+
+    let mut config = ConfigPort::all(Direction::Input);
+    config.set(PortPin::P3, Direction::Output);
+
+    assert_eq!(config.into_raw(), 0xF7);
+    assert_eq!(config.get(PortPin::P3), Direction::Output);
+    assert_eq!(config.get(PortPin::P4), Direction::Input);
+    assert_eq!(ConfigPort::register(Port::Port1), Register::ConfigPort1);
+
+    // Defaults are the values after a reset
+    assert_eq!(ConfigPort::default().into_raw(), 0x00);
+    assert_eq!(LedModeSwitch::default().into_raw(), 0xFF);
+    assert_eq!(LedModeSwitch::default().get(PortPin::P0), LedMode::Gpio);
+}
@@ -0,0 +1,84 @@
+use aw9523b::dump::RegisterChange;
+use aw9523b::sim::{Simulator, DEVICE_ID};
+use aw9523b::{Aw9523b, DriveCurrent, Pin, PinMode, PinState, Port, Port0OutputDriveMode, PortPin, Register};
+use embassy_futures::block_on;
+
+const ADDR: u8 = 0x5B;
+
+const BUTTON: Pin = Pin(Port::Port0, PortPin::P2);
+const ENABLE: Pin = Pin(Port::Port0, PortPin::P7);
+const LED: Pin = Pin(Port::Port1, PortPin::P0);
+
+fn setup() -> (Simulator, Aw9523b<Simulator>) {
+    let sim = Simulator::new(ADDR);
+    let driver = Aw9523b::new(sim.clone(), ADDR);
+    (sim, driver)
+}
+
+#[test]
+fn snapshot_is_decoded() {
+    let (sim, mut driver) = setup();
+    block_on(driver.set_pin_config(BUTTON, PinMode::Input)).unwrap();
+    block_on(driver.enable_pin_interrupt(BUTTON, true)).unwrap();
+    block_on(driver.set_pin_config(ENABLE, PinMode::Output)).unwrap();
+    block_on(driver.set_pin_output_state(ENABLE, PinState::High)).unwrap();
+    block_on(driver.set_pin_config(LED, PinMode::Led)).unwrap();
+    block_on(driver.set_pin_led_pwm(LED, 0x80)).unwrap();
+    block_on(driver.set_drive_current(DriveCurrent::Low)).unwrap();
+    sim.set_input_levels(Port::Port0, 0x00);
+    sim.clear_transactions();
+
+    let snapshot = block_on(driver.snapshot()).unwrap();
+    assert_eq!(sim.transactions().len(), 2);
+
+    assert_eq!(snapshot.device_id(), DEVICE_ID);
+    assert_eq!(snapshot.pin_mode(BUTTON), PinMode::Input);
+    assert_eq!(snapshot.pin_mode(ENABLE), PinMode::Output);
+    assert_eq!(snapshot.pin_mode(LED), PinMode::Led);
+    assert!(snapshot.interrupt(BUTTON));
+    assert_eq!(snapshot.input(BUTTON), PinState::Low);
+    assert_eq!(snapshot.output(ENABLE), PinState::High);
+    assert_eq!(snapshot.dim_level(LED), Some(0x80));
+    assert_eq!(snapshot.drive_current(), DriveCurrent::Low);
+    assert_eq!(snapshot.port0_drive_mode(), Port0OutputDriveMode::OpenDrain);
+}
+
+#[test]
+fn dim_levels_are_known_after_reset() {
+    let (_sim, mut driver) = setup();
+    let snapshot = block_on(driver.snapshot()).unwrap();
+    assert_eq!(snapshot.dim_level(LED), None);
+    assert_eq!(snapshot.register(Register::Dim0), None);
+
+    block_on(driver.software_reset()).unwrap();
+    let snapshot = block_on(driver.snapshot()).unwrap();
+    assert_eq!(snapshot.dim_level(LED), Some(0x00));
+}
+
+#[test]
+fn diff_shows_changed_registers() {
+    let (_sim, mut driver) = setup();
+    let before = block_on(driver.snapshot()).unwrap();
+    assert!(before.diff(&before).is_empty());
+
+    block_on(driver.set_pin_config(LED, PinMode::Led)).unwrap();
+    block_on(driver.set_pin_led_pwm(LED, 0x40)).unwrap();
+    let after = block_on(driver.snapshot()).unwrap();
+
+    let changes: Vec<_> = before.diff(&after).iter().collect();
+    assert_eq!(
+        changes,
+        [
+            RegisterChange {
+                register: Register::LedModeSwitchP1,
+                before: Some(0xFF),
+                after: Some(0xFE),
+            },
+            RegisterChange {
+                register: Register::Dim0,
+                before: None,
+                after: Some(0x40),
+            },
+        ]
+    );
+}
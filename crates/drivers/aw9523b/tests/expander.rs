@@ -0,0 +1,49 @@
+#![feature(async_fn_in_trait)]
+
+use aw9523b::sim::Simulator;
+use aw9523b::{dim_register, Aw9523b, Pin, Port, PortPin, Register};
+use embassy_futures::block_on;
+use io_expander::{Direction, LedDimming, PinState};
+
+const ADDR: u8 = 0x5B;
+
+const BUTTON: Pin = Pin(Port::Port0, PortPin::P2);
+const ENABLE: Pin = Pin(Port::Port0, PortPin::P6);
+const LED: Pin = Pin(Port::Port1, PortPin::P0);
+
+struct NoDelay;
+
+impl embedded_hal_async::delay::DelayUs for NoDelay {
+    async fn delay_us(&mut self, _us: u32) {}
+
+    async fn delay_ms(&mut self, _ms: u32) {}
+}
+
+/// Uses the device through the generic interface only.
+async fn exercise<X: LedDimming>(expander: &mut X) -> Result<PinState, X::Error> {
+    expander.reset(&mut NoDelay).await?;
+    expander.set_pin_direction(BUTTON, Direction::Input).await?;
+    expander.set_pin_interrupt(BUTTON, true).await?;
+    expander.set_pin_direction(ENABLE, Direction::Output).await?;
+    expander.write_pin(ENABLE, PinState::High).await?;
+    expander.set_pin_led_mode(LED, true).await?;
+    expander.set_led_level(LED, 0x80).await?;
+    expander.read_pin(BUTTON).await
+}
+
+#[test]
+fn generic_interface() {
+    let sim = Simulator::new(ADDR);
+    let mut driver = Aw9523b::new(sim.clone(), ADDR);
+    sim.set_register(Register::IntPort0, 0xFF);
+    sim.set_input_levels(Port::Port0, 0x00);
+
+    assert_eq!(block_on(exercise(&mut driver)).unwrap(), PinState::Low);
+
+    // Reset in software without a reset pin, then configured
+    assert_eq!(sim.register(Register::IntPort0), 0x00);
+    assert_eq!(sim.register(Register::ConfigPort0), 0x04);
+    assert_eq!(sim.register(Register::OutputPort0) & 0x40, 0x40);
+    assert_eq!(sim.register(Register::LedModeSwitchP1), 0xFE);
+    assert_eq!(sim.register(dim_register(LED)), 0x80);
+}
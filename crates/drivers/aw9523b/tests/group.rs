@@ -0,0 +1,93 @@
+use aw9523b::group::{Aw9523bGroup, GroupPin};
+use aw9523b::sim::Simulator;
+use aw9523b::{dim_register, Address, Aw9523b, AwError, Pin, PinMode, PinState, Port, PortPin, Register};
+use embassy_futures::block_on;
+
+const FRONT: Address = Address::from_straps(PinState::Low, PinState::Low);
+const BACK: Address = Address::from_straps(PinState::High, PinState::High);
+
+fn setup() -> ([Simulator; 2], Aw9523bGroup<Simulator, 2>) {
+    let sims = [Simulator::new(FRONT.addr()), Simulator::new(BACK.addr())];
+    let group = Aw9523bGroup::new([
+        Aw9523b::new(sims[0].clone(), FRONT),
+        Aw9523b::new(sims[1].clone(), BACK),
+    ]);
+    (sims, group)
+}
+
+#[test]
+fn strap_addresses() {
+    let expected = [
+        (PinState::Low, PinState::Low, 0x58),
+        (PinState::High, PinState::Low, 0x59),
+        (PinState::Low, PinState::High, 0x5A),
+        (PinState::High, PinState::High, 0x5B),
+    ];
+
+    for (ad0, ad1, addr) in expected {
+        let address = Address::from_straps(ad0, ad1);
+        assert_eq!(address.addr(), addr);
+        assert_eq!((address.ad0(), address.ad1()), (ad0, ad1));
+        assert_eq!(Address::from_u8(addr), Some(address));
+    }
+
+    assert_eq!(Address::from_u8(0x57), None);
+    assert_eq!(Address::from_u8(0x5C), None);
+}
+
+#[test]
+fn group_pin_index_round_trip() {
+    assert_eq!(GroupPin::from_index(0), GroupPin::new(0, Pin(Port::Port0, PortPin::P0)));
+    assert_eq!(GroupPin::from_index(10), GroupPin::new(0, Pin(Port::Port1, PortPin::P2)));
+    assert_eq!(GroupPin::from_index(23), GroupPin::new(1, Pin(Port::Port0, PortPin::P7)));
+
+    for index in 0..64 {
+        assert_eq!(GroupPin::from_index(index).index(), index);
+    }
+    assert_eq!(GroupPin::from_index(63).mask(), 1 << 63);
+}
+
+#[test]
+#[should_panic]
+fn group_pin_past_the_last_device() {
+    GroupPin::from_index(64).mask();
+}
+
+#[test]
+fn pins_map_to_their_device() {
+    let (sims, mut group) = setup();
+    block_on(group.probe()).unwrap();
+
+    let led = GroupPin::from_index(16 + 8);
+    block_on(group.set_pin_config(led, PinMode::Led)).unwrap();
+    block_on(group.set_pin_led_pwm(led, 0x80)).unwrap();
+
+    assert_eq!(sims[0].register(Register::LedModeSwitchP1), 0xFF);
+    assert_eq!(sims[1].register(Register::LedModeSwitchP1), 0xFE);
+    assert_eq!(sims[1].register(dim_register(led.pin)), 0x80);
+}
+
+#[test]
+fn read_inputs_of_all_devices() {
+    let (sims, mut group) = setup();
+    for sim in &sims {
+        sim.set_register(Register::ConfigPort0, 0xFF);
+        sim.set_register(Register::ConfigPort1, 0xFF);
+    }
+
+    sims[0].set_input_levels(Port::Port0, 0x00);
+    sims[0].set_input_levels(Port::Port1, 0x01);
+    sims[1].set_input_levels(Port::Port0, 0x80);
+    sims[1].set_input_levels(Port::Port1, 0x00);
+
+    let inputs = block_on(group.read_inputs()).unwrap();
+    assert_eq!(inputs, GroupPin::from_index(8).mask() | GroupPin::from_index(23).mask());
+    assert_eq!(block_on(group.read_pin(GroupPin::from_index(23))).unwrap(), PinState::High);
+}
+
+#[test]
+fn missing_device() {
+    let sim = Simulator::new(FRONT.addr());
+    let mut group = Aw9523bGroup::new([Aw9523b::new(sim.clone(), FRONT), Aw9523b::new(sim, BACK)]);
+    assert!(matches!(block_on(group.probe()), Err(AwError::I2c { .. })));
+}
@@ -0,0 +1,107 @@
+use aw9523b::keypad::{KeySet, Keypad};
+use aw9523b::sim::Simulator;
+use aw9523b::{Aw9523b, Pin, Port, PortPin, Register};
+use embassy_futures::block_on;
+
+const ADDR: u8 = 0x5B;
+
+const ROWS: [Pin; 3] = [
+    Pin(Port::Port1, PortPin::P0),
+    Pin(Port::Port1, PortPin::P1),
+    Pin(Port::Port1, PortPin::P2),
+];
+
+const COLUMNS: [Pin; 3] = [
+    Pin(Port::Port0, PortPin::P0),
+    Pin(Port::Port0, PortPin::P1),
+    Pin(Port::Port0, PortPin::P2),
+];
+
+const KEYPAD: Keypad<3, 3> = Keypad::new(ROWS, COLUMNS);
+
+fn setup() -> (Simulator, Aw9523b<Simulator>) {
+    let sim = Simulator::new(ADDR);
+    let mut driver = Aw9523b::new(sim.clone(), ADDR);
+    block_on(KEYPAD.configure(&mut driver)).unwrap();
+    (sim, driver)
+}
+
+fn press(sim: &Simulator, row: usize, column: usize) {
+    sim.close_switch(ROWS[row], COLUMNS[column]);
+}
+
+#[test]
+fn key_set() {
+    let mut keys = KeySet::empty();
+    assert!(keys.is_empty());
+    assert_eq!(keys.single(), None);
+
+    keys.insert(5);
+    assert_eq!(keys.single(), Some(5));
+
+    keys.insert(63);
+    assert!(keys.contains(63));
+    assert!(!keys.contains(64));
+    assert_eq!(keys.len(), 2);
+    assert_eq!(keys.single(), None);
+    assert_eq!(keys.iter().collect::<Vec<_>>(), [5, 63]);
+}
+
+#[test]
+fn idle_rows_driven_low_with_column_interrupts() {
+    let (sim, mut driver) = setup();
+
+    assert_eq!(sim.register(Register::ConfigPort0) & 0x07, 0x07);
+    assert_eq!(sim.register(Register::ConfigPort1) & 0x07, 0x00);
+    assert_eq!(sim.register(Register::OutputPort1) & 0x07, 0x00);
+    assert_eq!(sim.register(Register::IntPort0) & 0x07, 0x00);
+    assert_eq!(sim.register(Register::IntPort1) & 0x07, 0x07);
+
+    // Reading the inputs clears the interrupt raised while configuring
+    block_on(driver.read_port(Port::Port0)).unwrap();
+    block_on(driver.read_port(Port::Port1)).unwrap();
+    assert!(!sim.is_interrupt_asserted());
+
+    press(&sim, 2, 1);
+    assert!(sim.is_interrupt_asserted());
+}
+
+#[test]
+fn scan_single_key() {
+    let (sim, mut driver) = setup();
+
+    let scan = block_on(KEYPAD.scan(&mut driver)).unwrap();
+    assert!(scan.keys().is_empty());
+
+    press(&sim, 1, 2);
+    let scan = block_on(KEYPAD.scan(&mut driver)).unwrap();
+    assert!(!scan.is_ghosted());
+    assert_eq!(scan.keys().single(), Some(Keypad::<3, 3>::key(1, 2)));
+
+    // Rows are back to their idle direction
+    assert_eq!(sim.register(Register::ConfigPort1) & 0x07, 0x00);
+}
+
+#[test]
+fn scan_keys_in_same_column() {
+    let (sim, mut driver) = setup();
+    press(&sim, 0, 1);
+    press(&sim, 2, 1);
+
+    let scan = block_on(KEYPAD.scan(&mut driver)).unwrap();
+    assert!(!scan.is_ghosted());
+    assert_eq!(scan.keys().iter().collect::<Vec<_>>(), [1, 7]);
+}
+
+#[test]
+fn three_corners_of_a_rectangle_are_ghosted() {
+    let (sim, mut driver) = setup();
+    press(&sim, 0, 0);
+    press(&sim, 0, 2);
+    press(&sim, 1, 0);
+
+    let scan = block_on(KEYPAD.scan(&mut driver)).unwrap();
+    assert!(scan.is_ghosted());
+    // The fourth corner appears pressed too
+    assert!(scan.keys().contains(Keypad::<3, 3>::key(1, 2)));
+}
@@ -0,0 +1,96 @@
+use aw9523b::sim::{Op, Simulator};
+use aw9523b::{Aw9523b, Pin, PinMode, PinSet, Port, PortPin, Register};
+use embassy_futures::block_on;
+
+const ADDR: u8 = 0x5B;
+
+const AMP_ENABLE: Pin = Pin(Port::Port0, PortPin::P1);
+const DAC_ENABLE: Pin = Pin(Port::Port1, PortPin::P6);
+const MUTE: Pin = Pin(Port::Port1, PortPin::P7);
+
+const ENABLES: PinSet = PinSet::empty().with(AMP_ENABLE).with(DAC_ENABLE);
+
+fn setup() -> (Simulator, Aw9523b<Simulator>) {
+    let sim = Simulator::new(ADDR);
+    let driver = Aw9523b::new(sim.clone(), ADDR);
+    (sim, driver)
+}
+
+#[test]
+fn pin_set_bits() {
+    assert_eq!(ENABLES, PinSet(0x4002));
+    assert!(ENABLES.contains(DAC_ENABLE));
+    assert!(!ENABLES.contains(MUTE));
+    assert_eq!(ENABLES.port(Port::Port0), 0x02);
+    assert_eq!(ENABLES.port(Port::Port1), 0x40);
+    assert_eq!(ENABLES.len(), 2);
+    assert_eq!(PinSet::from_port(Port::Port1, 0xC0), PinSet::from(DAC_ENABLE) | PinSet::from(MUTE));
+
+    let mut pins = ENABLES;
+    pins.remove(AMP_ENABLE);
+    pins.insert(MUTE);
+    assert_eq!(pins & !ENABLES, PinSet::from(MUTE));
+}
+
+#[test]
+fn both_ports_in_one_burst() {
+    let (sim, mut driver) = setup();
+    sim.set_register(Register::OutputPort0, 0x80);
+    sim.set_register(Register::OutputPort1, 0x01);
+
+    block_on(driver.set_pins(ENABLES)).unwrap();
+
+    assert_eq!(sim.register(Register::OutputPort0), 0x82);
+    assert_eq!(sim.register(Register::OutputPort1), 0x41);
+    let ops: Vec<_> = sim.transactions().into_iter().map(|t| t.ops).collect();
+    assert_eq!(
+        ops,
+        [
+            vec![Op::Write(vec![0x02]), Op::Read(vec![0x80, 0x01])],
+            vec![Op::Write(vec![0x02]), Op::Write(vec![0x82, 0x41])],
+        ]
+    );
+}
+
+#[test]
+fn known_outputs_are_not_read_back() {
+    let (sim, mut driver) = setup();
+    block_on(driver.set_pins(ENABLES | PinSet::from(MUTE))).unwrap();
+    sim.clear_transactions();
+
+    // Only port 1 changes
+    block_on(driver.clear_pins(PinSet::from(MUTE))).unwrap();
+    assert_eq!(sim.register(Register::OutputPort1), 0x40);
+    let ops: Vec<_> = sim.transactions().into_iter().map(|t| t.ops).collect();
+    assert_eq!(ops, [vec![Op::Write(vec![0x03]), Op::Write(vec![0x40])]]);
+
+    // Nothing changes
+    sim.clear_transactions();
+    block_on(driver.write_pins(ENABLES, ENABLES)).unwrap();
+    assert!(sim.transactions().is_empty());
+}
+
+#[test]
+fn configure_pins() {
+    let (sim, mut driver) = setup();
+    block_on(driver.software_reset()).unwrap();
+    sim.clear_transactions();
+
+    block_on(driver.configure_pins(ENABLES, PinMode::Output)).unwrap();
+    assert_eq!(sim.register(Register::ConfigPort0), 0x00);
+    assert_eq!(sim.register(Register::LedModeSwitchP0), 0xFF);
+    // Output and GPIO modes are the defaults after a reset
+    assert!(sim.transactions().is_empty());
+
+    block_on(driver.configure_pins(ENABLES | PinSet::from(MUTE), PinMode::Input)).unwrap();
+    assert_eq!(sim.register(Register::ConfigPort0), 0x02);
+    assert_eq!(sim.register(Register::ConfigPort1), 0xC0);
+    assert_eq!(sim.transactions().len(), 1);
+
+    block_on(driver.configure_pins(ENABLES, PinMode::Led)).unwrap();
+    assert_eq!(sim.register(Register::ConfigPort0), 0x00);
+    assert_eq!(sim.register(Register::ConfigPort1), 0x80);
+    assert_eq!(sim.register(Register::LedModeSwitchP0), 0xFD);
+    assert_eq!(sim.register(Register::LedModeSwitchP1), 0xBF);
+    assert_eq!(sim.transactions().len(), 3);
+}
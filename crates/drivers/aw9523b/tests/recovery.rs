@@ -0,0 +1,91 @@
+use aw9523b::recovery::Health;
+use aw9523b::sim::Simulator;
+use aw9523b::{Aw9523b, AwError, DriveCurrent, Pin, PinMode, Port, PortPin, Register};
+use embassy_futures::block_on;
+
+const ADDR: u8 = 0x5B;
+
+const BUTTON: Pin = Pin(Port::Port0, PortPin::P2);
+const LED: Pin = Pin(Port::Port1, PortPin::P0);
+
+fn configure(driver: &mut Aw9523b<Simulator>) {
+    block_on(driver.set_pin_config(BUTTON, PinMode::Input)).unwrap();
+    block_on(driver.set_pin_config(LED, PinMode::Led)).unwrap();
+    block_on(driver.set_drive_current(DriveCurrent::Mid)).unwrap();
+    block_on(driver.set_pin_led_pwm(LED, 0x80)).unwrap();
+}
+
+#[test]
+fn probe() {
+    let sim = Simulator::new(ADDR);
+    let mut driver = Aw9523b::new(sim.clone(), ADDR);
+    block_on(driver.probe()).unwrap();
+
+    sim.set_register(Register::Id, 0x42);
+    assert!(matches!(block_on(driver.probe()), Err(AwError::UnexpectedDeviceId(0x42))));
+
+    let mut absent = Aw9523b::new(sim.clone(), 0x58);
+    assert!(matches!(block_on(absent.probe()), Err(AwError::I2c { .. })));
+}
+
+#[test]
+fn healthy_device_is_left_alone() {
+    let sim = Simulator::new(ADDR);
+    let mut driver = Aw9523b::new(sim.clone(), ADDR);
+    configure(&mut driver);
+
+    sim.clear_transactions();
+    assert_eq!(block_on(driver.check_and_recover()).unwrap(), Health::Ok);
+
+    // Probe and two burst reads of the configuration registers, no writes
+    assert_eq!(sim.transactions().len(), 3);
+}
+
+#[test]
+fn configuration_restored_after_power_cycle() {
+    let sim = Simulator::new(ADDR);
+    let mut driver = Aw9523b::new(sim.clone(), ADDR);
+    configure(&mut driver);
+
+    let expected: Vec<_> = [
+        Register::ConfigPort0,
+        Register::ConfigPort1,
+        Register::Ctl,
+        Register::LedModeSwitchP0,
+        Register::LedModeSwitchP1,
+        Register::Dim0,
+    ]
+    .into_iter()
+    .map(|r| sim.register(r))
+    .collect();
+
+    sim.power_cycle();
+    assert_eq!(sim.register(Register::Dim0), 0x00);
+
+    assert_eq!(block_on(driver.check_and_recover()).unwrap(), Health::Restored);
+    assert_eq!(block_on(driver.check_and_recover()).unwrap(), Health::Ok);
+
+    let restored: Vec<_> = [
+        Register::ConfigPort0,
+        Register::ConfigPort1,
+        Register::Ctl,
+        Register::LedModeSwitchP0,
+        Register::LedModeSwitchP1,
+        Register::Dim0,
+    ]
+    .into_iter()
+    .map(|r| sim.register(r))
+    .collect();
+    assert_eq!(restored, expected);
+}
+
+#[test]
+fn software_reset_forgets_the_configuration() {
+    let sim = Simulator::new(ADDR);
+    let mut driver = Aw9523b::new(sim.clone(), ADDR);
+    configure(&mut driver);
+
+    block_on(driver.software_reset()).unwrap();
+    assert_eq!(block_on(driver.check_and_recover()).unwrap(), Health::Ok);
+    assert_eq!(sim.register(Register::LedModeSwitchP1), 0xFF);
+}
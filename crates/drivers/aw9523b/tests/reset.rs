@@ -0,0 +1,140 @@
+#![feature(async_fn_in_trait)]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use aw9523b::config::Aw9523bConfig;
+use aw9523b::recovery::Health;
+use aw9523b::sim::Simulator;
+use aw9523b::{dim_register, Aw9523b, AwError, DriveCurrent, Pin, PinMode, Port, PortPin, Register};
+use embassy_futures::block_on;
+use embedded_hal::digital::{ErrorType, OutputPin};
+use embedded_hal_async::delay::DelayUs;
+
+const ADDR: u8 = 0x5B;
+
+const BUTTON: Pin = Pin(Port::Port0, PortPin::P2);
+const LED: Pin = Pin(Port::Port1, PortPin::P0);
+
+const CONFIG: Aw9523bConfig = Aw9523bConfig::new()
+    .with_pin_mode(BUTTON, PinMode::Input)
+    .with_interrupt(BUTTON, true)
+    .with_pin_mode(LED, PinMode::Led)
+    .with_dim_level(LED, 0x40)
+    .with_drive_current(DriveCurrent::Mid);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Event {
+    Low,
+    High,
+    Delay(u32),
+}
+
+type Log = Rc<RefCell<Vec<Event>>>;
+
+/// RSTN line wired to the simulated device, which is reset on the rising edge.
+struct ResetPin {
+    sim: Simulator,
+    log: Log,
+}
+
+impl ErrorType for ResetPin {
+    type Error = core::convert::Infallible;
+}
+
+impl OutputPin for ResetPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.log.borrow_mut().push(Event::Low);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        if self.log.borrow().last() != Some(&Event::High) {
+            self.sim.power_cycle();
+        }
+        self.log.borrow_mut().push(Event::High);
+        Ok(())
+    }
+}
+
+struct Delay(Log);
+
+impl DelayUs for Delay {
+    async fn delay_us(&mut self, us: u32) {
+        self.0.borrow_mut().push(Event::Delay(us));
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        self.0.borrow_mut().push(Event::Delay(ms * 1000));
+    }
+}
+
+fn setup() -> (Simulator, Log, Aw9523b<Simulator, ResetPin>) {
+    let sim = Simulator::new(ADDR);
+    let log = Log::default();
+    let reset_pin = ResetPin {
+        sim: sim.clone(),
+        log: log.clone(),
+    };
+    let driver = Aw9523b::new(sim.clone(), ADDR).with_reset_pin(reset_pin);
+    (sim, log, driver)
+}
+
+#[test]
+fn hardware_reset_timing() {
+    let (_, log, mut driver) = setup();
+    block_on(driver.hardware_reset(&mut Delay(log.clone()))).unwrap();
+
+    let log = log.borrow();
+    let [Event::Low, Event::Delay(pulse), Event::High, Event::Delay(recovery)] = log[..] else {
+        panic!("unexpected reset sequence {:?}", log);
+    };
+
+    // RSTN must be held low for at least 20 µs
+    assert!(pulse >= 20);
+    assert!(recovery > 0);
+}
+
+#[test]
+fn hardware_reset_clears_registers() {
+    let (sim, log, mut driver) = setup();
+    block_on(driver.set_pin_config(LED, PinMode::Led)).unwrap();
+    assert_eq!(sim.register(Register::LedModeSwitchP1), 0xFE);
+
+    block_on(driver.hardware_reset(&mut Delay(log))).unwrap();
+    assert_eq!(sim.register(Register::LedModeSwitchP1), 0xFF);
+
+    // The configuration was reset on purpose, there is nothing to restore
+    assert!(block_on(driver.is_configured()).unwrap());
+    assert_eq!(block_on(driver.check_and_recover()).unwrap(), Health::Ok);
+}
+
+#[test]
+fn reset_and_configure() {
+    let (sim, log, mut driver) = setup();
+    sim.set_register(Register::ConfigPort1, 0xFF);
+
+    block_on(driver.reset_and_configure(&mut Delay(log), &CONFIG)).unwrap();
+
+    assert_eq!(sim.register(Register::ConfigPort0), 0x04);
+    assert_eq!(sim.register(Register::ConfigPort1), 0x00);
+    assert_eq!(sim.register(Register::IntPort0), 0x00);
+    assert_eq!(sim.register(Register::Ctl), 0x02);
+    assert_eq!(sim.register(Register::LedModeSwitchP0), 0xFF);
+    assert_eq!(sim.register(Register::LedModeSwitchP1), 0xFE);
+    assert_eq!(sim.register(dim_register(LED)), 0x40);
+    assert_eq!(block_on(driver.get_pin_config(BUTTON)).unwrap(), PinMode::Input);
+}
+
+#[test]
+fn reset_and_configure_checks_device() {
+    let (sim, log, _) = setup();
+    let reset_pin = ResetPin {
+        sim: sim.clone(),
+        log: log.clone(),
+    };
+    let mut absent = Aw9523b::new(sim, 0x58).with_reset_pin(reset_pin);
+
+    let result = block_on(absent.reset_and_configure(&mut Delay(log), &CONFIG));
+    assert!(matches!(result, Err(AwError::I2c { .. })));
+}
@@ -0,0 +1,64 @@
+use aw9523b::error::{Context, Operation};
+use aw9523b::sim::{SimError, Simulator};
+use aw9523b::{Aw9523b, AwError, BasicOps, Register, RetryPolicy};
+use embassy_futures::block_on;
+
+const ADDR: u8 = 0x5B;
+
+fn setup(retry_policy: RetryPolicy) -> (Simulator, Aw9523b<Simulator>) {
+    let sim = Simulator::new(ADDR);
+    let driver = Aw9523b::new(sim.clone(), ADDR).with_retry_policy(retry_policy);
+    (sim, driver)
+}
+
+#[test]
+fn errors_are_reported_with_context() {
+    let (sim, mut driver) = setup(RetryPolicy::default());
+    sim.fail_next(1, SimError::NoAcknowledge);
+
+    let error = block_on(driver.write_registers(Register::ConfigPort0, &[0x00, 0x00])).unwrap_err();
+    assert_eq!(error.i2c_error(), Some(&SimError::NoAcknowledge));
+    assert_eq!(
+        error.context(),
+        Some(&Context {
+            register: Register::ConfigPort0,
+            operation: Operation::Write,
+            attempts: 1,
+        })
+    );
+}
+
+#[test]
+fn transient_errors_are_retried() {
+    let (sim, mut driver) = setup(RetryPolicy::new(3));
+    sim.fail_next(1, SimError::NoAcknowledge);
+    sim.fail_next(1, SimError::ArbitrationLoss);
+
+    assert_eq!(block_on(driver.read_register(Register::Id)).unwrap(), 0x23);
+    assert_eq!(sim.transactions().len(), 3);
+}
+
+#[test]
+fn retries_are_bounded() {
+    let (sim, mut driver) = setup(RetryPolicy::new(3));
+    sim.fail_next(5, SimError::NoAcknowledge);
+
+    let result = block_on(driver.read_register(Register::InputPort1));
+    let Err(AwError::I2c { context, .. }) = result else {
+        panic!("unexpected result {result:?}");
+    };
+    assert_eq!(context.register, Register::InputPort1);
+    assert_eq!(context.operation, Operation::Read);
+    assert_eq!(context.attempts, 3);
+    assert_eq!(sim.transactions().len(), 3);
+}
+
+#[test]
+fn only_selected_errors_are_retried() {
+    let (sim, mut driver) = setup(RetryPolicy::new(3).with_arbitration_loss(false));
+    sim.fail_next(1, SimError::ArbitrationLoss);
+
+    let error = block_on(driver.write_register(Register::Dim0, 0x10)).unwrap_err();
+    assert_eq!(error.context().map(|context| context.attempts), Some(1));
+    assert_eq!(sim.register(Register::Dim0), 0x00);
+}
@@ -0,0 +1,154 @@
+use aw9523b::sim::{Op, Simulator, Transaction, DEVICE_ID};
+use aw9523b::{Aw9523b, AwError, BasicOps, Pin, PinMode, Port, PortPin, Register};
+use embassy_futures::block_on;
+use embedded_hal_async::i2c::I2c;
+
+const ADDR: u8 = 0x5B;
+
+fn setup() -> (Simulator, Aw9523b<Simulator>) {
+    let sim = Simulator::new(ADDR);
+    let driver = Aw9523b::new(sim.clone(), ADDR);
+    (sim, driver)
+}
+
+#[test]
+fn power_on_defaults() {
+    let (sim, mut driver) = setup();
+
+    assert_eq!(block_on(driver.read_device_id()).unwrap(), DEVICE_ID);
+    assert_eq!(sim.register(Register::OutputPort1), 0x00);
+    assert_eq!(sim.register(Register::ConfigPort0), 0x00);
+    assert_eq!(sim.register(Register::ConfigPort1), 0x00);
+    assert_eq!(sim.register(Register::IntPort0), 0x00);
+    assert_eq!(sim.register(Register::IntPort1), 0x00);
+    assert_eq!(sim.register(Register::Ctl), 0x00);
+    assert_eq!(sim.register(Register::LedModeSwitchP0), 0xFF);
+    assert_eq!(sim.register(Register::LedModeSwitchP1), 0xFF);
+    assert_eq!(sim.register(Register::Dim0), 0x00);
+}
+
+#[test]
+fn output_port0_reset_value_follows_strapping() {
+    let sim = Simulator::new(ADDR).with_output_port0_reset_value(0xA5);
+    assert_eq!(sim.register(Register::OutputPort0), 0xA5);
+
+    sim.set_register(Register::OutputPort0, 0x00);
+    sim.power_cycle();
+    assert_eq!(sim.register(Register::OutputPort0), 0xA5);
+}
+
+#[test]
+fn read_only_registers_are_protected() {
+    let (sim, mut driver) = setup();
+
+    let result = block_on(driver.write_register(Register::Id, 0x00));
+    assert!(matches!(result, Err(AwError::WriteToReadOnly(Register::Id))));
+    assert!(sim.transactions().is_empty());
+
+    // Bypass the driver, the device must ignore the write
+    let mut bus = sim.clone();
+    block_on(bus.write(ADDR, &[Register::Id.addr(), 0x00])).unwrap();
+    assert_eq!(sim.register(Register::Id), DEVICE_ID);
+}
+
+#[test]
+fn write_only_registers_read_as_zero() {
+    let (sim, mut driver) = setup();
+
+    block_on(driver.set_pin_led_pwm(Pin(Port::Port1, PortPin::P0), 0x80)).unwrap();
+    assert_eq!(sim.register(Register::Dim0), 0x80);
+    assert_eq!(block_on(driver.read_register(Register::Dim0)).unwrap(), 0x00);
+}
+
+#[test]
+fn software_reset_restores_defaults() {
+    let (sim, mut driver) = setup();
+
+    block_on(driver.set_port_config(Port::Port0, 0xFF)).unwrap();
+    block_on(driver.set_port_led_mode_switch(Port::Port1, 0x00)).unwrap();
+    block_on(driver.write_register(Register::Dim5, 0x42)).unwrap();
+
+    block_on(driver.software_reset()).unwrap();
+
+    assert_eq!(sim.register(Register::ConfigPort0), 0x00);
+    assert_eq!(sim.register(Register::LedModeSwitchP1), 0xFF);
+    assert_eq!(sim.register(Register::Dim5), 0x00);
+}
+
+#[test]
+fn auto_increment() {
+    let sim = Simulator::new(ADDR);
+    let mut bus = sim.clone();
+
+    block_on(bus.write(ADDR, &[Register::Dim0.addr(), 1, 2, 3, 4])).unwrap();
+    assert_eq!(sim.register(Register::Dim0), 1);
+    assert_eq!(sim.register(Register::Dim1), 2);
+    assert_eq!(sim.register(Register::Dim2), 3);
+    assert_eq!(sim.register(Register::Dim3), 4);
+
+    let mut buffer = [0u8; 4];
+    block_on(bus.write_read(ADDR, &[Register::OutputPort0.addr()], &mut buffer)).unwrap();
+    assert_eq!(buffer, [0x00, 0x00, 0x00, 0x00]);
+
+    block_on(bus.write(ADDR, &[Register::ConfigPort0.addr(), 0x0F, 0xF0])).unwrap();
+    block_on(bus.write_read(ADDR, &[Register::ConfigPort0.addr()], &mut buffer[..2])).unwrap();
+    assert_eq!(buffer[..2], [0x0F, 0xF0]);
+}
+
+#[test]
+fn interrupt_asserted_on_input_change_and_cleared_on_read() {
+    let (sim, mut driver) = setup();
+    let pin = Pin(Port::Port0, PortPin::P3);
+
+    block_on(driver.set_pin_config(pin, PinMode::Input)).unwrap();
+    block_on(driver.set_port_interrupt_config(Port::Port0, !0x08)).unwrap();
+    block_on(driver.read_port(Port::Port0)).unwrap();
+    assert!(!sim.is_interrupt_asserted());
+
+    // Changes on pins with the interrupt disabled are ignored
+    sim.set_input_levels(Port::Port0, !0x10);
+    assert!(!sim.is_interrupt_asserted());
+
+    sim.set_input_levels(Port::Port0, !0x18);
+    assert!(sim.is_interrupt_asserted());
+
+    // Reading the other port does not clear the interrupt
+    block_on(driver.read_port(Port::Port1)).unwrap();
+    assert!(sim.is_interrupt_asserted());
+
+    block_on(driver.read_port(Port::Port0)).unwrap();
+    assert!(!sim.is_interrupt_asserted());
+}
+
+#[test]
+fn transaction_log() {
+    let (sim, mut driver) = setup();
+
+    block_on(driver.set_pin_led_pwm(Pin(Port::Port0, PortPin::P0), 0x10)).unwrap();
+    block_on(driver.read_device_id()).unwrap();
+
+    assert_eq!(
+        sim.transactions(),
+        [
+            Transaction {
+                address: ADDR,
+                ops: vec![Op::Write(vec![Register::Dim4.addr(), 0x10])],
+            },
+            Transaction {
+                address: ADDR,
+                ops: vec![Op::Write(vec![Register::Id.addr()]), Op::Read(vec![DEVICE_ID])],
+            },
+        ]
+    );
+
+    sim.clear_transactions();
+    assert!(sim.transactions().is_empty());
+}
+
+#[test]
+fn wrong_address_is_not_acknowledged() {
+    let sim = Simulator::new(ADDR);
+    let mut driver = Aw9523b::new(sim.clone(), 0x58);
+
+    assert!(matches!(block_on(driver.read_device_id()), Err(AwError::I2c { .. })));
+}
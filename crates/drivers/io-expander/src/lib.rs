@@ -0,0 +1,83 @@
+//! Interface shared by the GPIO expander drivers.
+//!
+//! The supported devices all have 16 pins split in two 8-bit ports, addressed with [`Pin`]. Code
+//! written against [`IoExpander`] works with any of them, and with [`LedDimming`] for the devices
+//! able to drive LEDs with a constant current.
+#![no_std]
+#![feature(async_fn_in_trait)]
+
+use embedded_hal_async::delay::DelayUs;
+
+pub use embedded_hal::digital::PinState;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pin(pub Port, pub PortPin);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Port {
+    Port0,
+    Port1,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PortPin {
+    P0,
+    P1,
+    P2,
+    P3,
+    P4,
+    P5,
+    P6,
+    P7,
+}
+
+/// Direction of a GPIO pin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Input,
+    Output,
+}
+
+pub trait IoExpander {
+    type Error;
+
+    /// Device-specific configuration of all the pins, see [`IoExpander::configure`].
+    type Config;
+
+    /// Checks that the device answers.
+    async fn probe(&mut self) -> Result<(), Self::Error>;
+
+    /// Brings every register of the device back to its default value.
+    async fn reset<D: DelayUs>(&mut self, delay: &mut D) -> Result<(), Self::Error>;
+
+    /// Writes a whole configuration to the device.
+    async fn configure(&mut self, config: &Self::Config) -> Result<(), Self::Error>;
+
+    /// Reads the level of the pins of a port.
+    async fn read_port(&mut self, port: Port) -> Result<u8, Self::Error>;
+
+    /// Sets the state of the output pins of a port.
+    async fn write_port(&mut self, port: Port, value: u8) -> Result<(), Self::Error>;
+
+    async fn read_pin(&mut self, pin: Pin) -> Result<PinState, Self::Error> {
+        let port = self.read_port(pin.0).await?;
+        Ok(PinState::from(port & (1 << pin.1 as u8) != 0))
+    }
+
+    async fn write_pin(&mut self, pin: Pin, state: PinState) -> Result<(), Self::Error>;
+
+    async fn set_pin_direction(&mut self, pin: Pin, direction: Direction) -> Result<(), Self::Error>;
+
+    /// Enables or disables the interrupt raised when the level of an input pin changes.
+    async fn set_pin_interrupt(&mut self, pin: Pin, enabled: bool) -> Result<(), Self::Error>;
+}
+
+/// Constant current LED driving, for the devices supporting it.
+pub trait LedDimming: IoExpander {
+    /// Switches a pin between LED mode and GPIO mode.
+    async fn set_pin_led_mode(&mut self, pin: Pin, enabled: bool) -> Result<(), Self::Error>;
+
+    /// Sets the current of an LED-mode pin, from 0 (off) to 255 (maximum current).
+    async fn set_led_level(&mut self, pin: Pin, level: u8) -> Result<(), Self::Error>;
+}
@@ -0,0 +1,82 @@
+//! Declarative configuration of the whole device.
+
+use crate::{Direction, Pin, PinState, Register, Tca6416, TcaError};
+
+/// Configuration of every pin of the device, buildable in a `const`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tca6416Config {
+    outputs: [u8; 2],
+    polarity_inversion: [u8; 2],
+    directions: [u8; 2],
+}
+
+impl Tca6416Config {
+    /// Creates a configuration matching the power-on state: every pin is an input and every
+    /// output register is high.
+    pub const fn new() -> Self {
+        Self {
+            outputs: [0xFF; 2],
+            polarity_inversion: [0x00; 2],
+            directions: [0xFF; 2],
+        }
+    }
+
+    pub const fn with_direction(mut self, pin: Pin, direction: Direction) -> Self {
+        // Config: 1 = input
+        let port = pin.0 as usize;
+        self.directions[port] = with_bit(self.directions[port], pin, matches!(direction, Direction::Input));
+        self
+    }
+
+    /// Sets the initial state of an output pin.
+    pub const fn with_output(mut self, pin: Pin, state: PinState) -> Self {
+        let port = pin.0 as usize;
+        self.outputs[port] = with_bit(self.outputs[port], pin, matches!(state, PinState::High));
+        self
+    }
+
+    /// Inverts the level read on an input pin.
+    pub const fn with_polarity_inversion(mut self, pin: Pin, inverted: bool) -> Self {
+        let port = pin.0 as usize;
+        self.polarity_inversion[port] = with_bit(self.polarity_inversion[port], pin, inverted);
+        self
+    }
+
+    pub const fn direction(&self, pin: Pin) -> Direction {
+        if self.directions[pin.0 as usize] & (1 << pin.1 as u8) != 0 {
+            Direction::Input
+        } else {
+            Direction::Output
+        }
+    }
+}
+
+impl Default for Tca6416Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const fn with_bit(value: u8, pin: Pin, set: bool) -> u8 {
+    let mask = 1 << pin.1 as u8;
+    if set {
+        value | mask
+    } else {
+        value & !mask
+    }
+}
+
+impl<I2C, E> Tca6416<I2C>
+where
+    I2C: embedded_hal_async::i2c::I2c + embedded_hal::i2c::ErrorType<Error = E>,
+{
+    /// Writes a whole configuration to the device, one transaction per register pair.
+    ///
+    /// The outputs are written before the directions so that output pins start at their
+    /// configured state.
+    pub async fn configure(&mut self, config: &Tca6416Config) -> Result<(), TcaError<E>> {
+        self.write_pair(Register::OutputPort0, config.outputs).await?;
+        self.write_pair(Register::PolarityInversionPort0, config.polarity_inversion).await?;
+        self.write_pair(Register::ConfigPort0, config.directions).await
+    }
+}
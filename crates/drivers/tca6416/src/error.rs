@@ -0,0 +1,8 @@
+#[derive(Debug)]
+pub enum Error<I2cError> {
+    /// I2C bus error.
+    I2c(I2cError),
+
+    /// The device raises its interrupt on every input pin, it cannot be disabled per pin.
+    InterruptMaskUnsupported,
+}
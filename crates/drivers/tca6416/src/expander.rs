@@ -0,0 +1,60 @@
+//! Implementation of the interface shared by the GPIO expander drivers.
+
+use embedded_hal_async::delay::DelayUs;
+use io_expander::IoExpander;
+
+use crate::config::Tca6416Config;
+use crate::{Direction, Pin, PinState, Port, Tca6416, TcaError};
+
+impl<I2C, E> IoExpander for Tca6416<I2C>
+where
+    I2C: embedded_hal_async::i2c::I2c + embedded_hal::i2c::ErrorType<Error = E>,
+{
+    type Error = TcaError<E>;
+    type Config = Tca6416Config;
+
+    /// The device has no identification register, any answer is accepted.
+    async fn probe(&mut self) -> Result<(), Self::Error> {
+        self.read_inputs().await?;
+        Ok(())
+    }
+
+    /// The reset pin of the device is not driven, the registers are written back to their
+    /// power-on value instead.
+    async fn reset<D: DelayUs>(&mut self, _delay: &mut D) -> Result<(), Self::Error> {
+        self.reset_to_defaults().await
+    }
+
+    async fn configure(&mut self, config: &Self::Config) -> Result<(), Self::Error> {
+        Tca6416::configure(self, config).await
+    }
+
+    async fn read_port(&mut self, port: Port) -> Result<u8, Self::Error> {
+        Tca6416::read_port(self, port).await
+    }
+
+    async fn write_port(&mut self, port: Port, value: u8) -> Result<(), Self::Error> {
+        self.set_port_output_state(port, value).await
+    }
+
+    async fn read_pin(&mut self, pin: Pin) -> Result<PinState, Self::Error> {
+        Tca6416::read_pin(self, pin).await
+    }
+
+    async fn write_pin(&mut self, pin: Pin, state: PinState) -> Result<(), Self::Error> {
+        self.set_pin_output_state(pin, state).await
+    }
+
+    async fn set_pin_direction(&mut self, pin: Pin, direction: Direction) -> Result<(), Self::Error> {
+        Tca6416::set_pin_direction(self, pin, direction).await
+    }
+
+    /// The interrupt of every input pin is always enabled, only enabling it succeeds.
+    async fn set_pin_interrupt(&mut self, _pin: Pin, enabled: bool) -> Result<(), Self::Error> {
+        if enabled {
+            Ok(())
+        } else {
+            Err(TcaError::InterruptMaskUnsupported)
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Driver for the TCA6416 and PCA9555 16-bit GPIO expanders.
+//!
+//! Both devices share the same register map. Registers come in pairs, one per port, and the
+//! register address toggles within its pair after each byte, so both ports of a pair are accessed
+//! in a single transaction but different pairs are not.
+#![no_std]
+#![feature(async_fn_in_trait)]
+
+pub use error::Error as TcaError;
+pub use io_expander::{Direction, Pin, PinState, Port, PortPin};
+
+mod error;
+mod expander;
+
+pub mod config;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Register {
+    InputPort0 = 0x00,
+    InputPort1 = 0x01,
+    OutputPort0 = 0x02,
+    OutputPort1 = 0x03,
+    PolarityInversionPort0 = 0x04,
+    PolarityInversionPort1 = 0x05,
+    ConfigPort0 = 0x06,
+    ConfigPort1 = 0x07,
+}
+
+impl Register {
+    pub const fn addr(self) -> u8 {
+        self as u8
+    }
+
+    /// Gets the register of a pair holding a port.
+    const fn of_port(self, port: Port) -> Register {
+        match (self, port) {
+            (Register::InputPort0 | Register::InputPort1, Port::Port0) => Register::InputPort0,
+            (Register::InputPort0 | Register::InputPort1, Port::Port1) => Register::InputPort1,
+            (Register::OutputPort0 | Register::OutputPort1, Port::Port0) => Register::OutputPort0,
+            (Register::OutputPort0 | Register::OutputPort1, Port::Port1) => Register::OutputPort1,
+            (Register::PolarityInversionPort0 | Register::PolarityInversionPort1, Port::Port0) => {
+                Register::PolarityInversionPort0
+            }
+            (Register::PolarityInversionPort0 | Register::PolarityInversionPort1, Port::Port1) => {
+                Register::PolarityInversionPort1
+            }
+            (Register::ConfigPort0 | Register::ConfigPort1, Port::Port0) => Register::ConfigPort0,
+            (Register::ConfigPort0 | Register::ConfigPort1, Port::Port1) => Register::ConfigPort1,
+        }
+    }
+}
+
+pub struct Tca6416<I2C> {
+    i2c: I2C,
+    addr: u8,
+}
+
+impl<I2C, E> Tca6416<I2C>
+where
+    I2C: embedded_hal_async::i2c::I2c + embedded_hal::i2c::ErrorType<Error = E>,
+{
+    /// Creates a new instance of a TCA6416 or PCA9555 driver.
+    ///
+    /// The address is `0x20` or `0x21` for the TCA6416 and `0x20..=0x27` for the PCA9555,
+    /// depending on the strapping of the address pins.
+    pub fn new(i2c: I2C, addr: u8) -> Self {
+        Self { i2c, addr }
+    }
+
+    /// Writes the power-on value of every register.
+    pub async fn reset_to_defaults(&mut self) -> Result<(), TcaError<E>> {
+        self.write_pair(Register::OutputPort0, [0xFF; 2]).await?;
+        self.write_pair(Register::PolarityInversionPort0, [0x00; 2]).await?;
+        self.write_pair(Register::ConfigPort0, [0xFF; 2]).await
+    }
+
+    /// Reads the port input state.
+    pub async fn read_port(&mut self, port: Port) -> Result<u8, TcaError<E>> {
+        self.read_register(Register::InputPort0.of_port(port)).await
+    }
+
+    /// Reads the input state of both ports, port 0 in the low byte.
+    pub async fn read_inputs(&mut self) -> Result<u16, TcaError<E>> {
+        Ok(u16::from_le_bytes(self.read_pair(Register::InputPort0).await?))
+    }
+
+    pub async fn read_pin(&mut self, pin: Pin) -> Result<PinState, TcaError<E>> {
+        let port = self.read_port(pin.0).await?;
+        Ok(PinState::from(port & (1 << pin.1 as u8) != 0))
+    }
+
+    pub async fn get_port_output_state(&mut self, port: Port) -> Result<u8, TcaError<E>> {
+        self.read_register(Register::OutputPort0.of_port(port)).await
+    }
+
+    pub async fn set_port_output_state(&mut self, port: Port, value: u8) -> Result<(), TcaError<E>> {
+        self.write_register(Register::OutputPort0.of_port(port), value).await
+    }
+
+    pub async fn set_pin_output_state(&mut self, pin: Pin, state: PinState) -> Result<(), TcaError<E>> {
+        let high = matches!(state, PinState::High);
+        self.modify_pin(Register::OutputPort0, pin, high).await
+    }
+
+    pub async fn get_port_config(&mut self, port: Port) -> Result<u8, TcaError<E>> {
+        self.read_register(Register::ConfigPort0.of_port(port)).await
+    }
+
+    pub async fn set_port_config(&mut self, port: Port, value: u8) -> Result<(), TcaError<E>> {
+        self.write_register(Register::ConfigPort0.of_port(port), value).await
+    }
+
+    pub async fn get_pin_direction(&mut self, pin: Pin) -> Result<Direction, TcaError<E>> {
+        // Config: 1 = input
+        let config = self.get_port_config(pin.0).await?;
+        if config & (1 << pin.1 as u8) != 0 {
+            Ok(Direction::Input)
+        } else {
+            Ok(Direction::Output)
+        }
+    }
+
+    pub async fn set_pin_direction(&mut self, pin: Pin, direction: Direction) -> Result<(), TcaError<E>> {
+        self.modify_pin(Register::ConfigPort0, pin, direction == Direction::Input).await
+    }
+
+    /// Inverts the level read on the input pins of a port set in `value`.
+    pub async fn set_port_polarity_inversion(&mut self, port: Port, value: u8) -> Result<(), TcaError<E>> {
+        self.write_register(Register::PolarityInversionPort0.of_port(port), value).await
+    }
+
+    async fn modify_pin(&mut self, register: Register, pin: Pin, set: bool) -> Result<(), TcaError<E>> {
+        let register = register.of_port(pin.0);
+        let mask = 1 << pin.1 as u8;
+        let value = self.read_register(register).await?;
+        let value = if set { value | mask } else { value & !mask };
+        self.write_register(register, value).await
+    }
+
+    async fn write_register(&mut self, register: Register, value: u8) -> Result<(), TcaError<E>> {
+        self.i2c.write(self.addr, &[register.addr(), value]).await.map_err(TcaError::I2c)
+    }
+
+    async fn read_register(&mut self, register: Register) -> Result<u8, TcaError<E>> {
+        let mut buffer = [0u8];
+        self.i2c.write_read(self.addr, &[register.addr()], &mut buffer).await.map_err(TcaError::I2c)?;
+        Ok(buffer[0])
+    }
+
+    /// Writes both registers of a pair, port 0 first.
+    async fn write_pair(&mut self, register: Register, values: [u8; 2]) -> Result<(), TcaError<E>> {
+        let register = register.of_port(Port::Port0);
+        self.i2c.write(self.addr, &[register.addr(), values[0], values[1]]).await.map_err(TcaError::I2c)
+    }
+
+    /// Reads both registers of a pair, port 0 first.
+    async fn read_pair(&mut self, register: Register) -> Result<[u8; 2], TcaError<E>> {
+        let register = register.of_port(Port::Port0);
+        let mut buffer = [0u8; 2];
+        self.i2c.write_read(self.addr, &[register.addr()], &mut buffer).await.map_err(TcaError::I2c)?;
+        Ok(buffer)
+    }
+}
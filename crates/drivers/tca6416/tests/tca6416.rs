@@ -0,0 +1,177 @@
+#![feature(async_fn_in_trait)]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use embassy_futures::block_on;
+use embedded_hal::i2c::{ErrorKind, ErrorType, NoAcknowledgeSource, Operation};
+use io_expander::IoExpander;
+use tca6416::config::Tca6416Config;
+use tca6416::{Direction, Pin, PinState, Port, PortPin, Register, Tca6416, TcaError};
+
+const ADDR: u8 = 0x20;
+
+const BUTTON: Pin = Pin(Port::Port0, PortPin::P3);
+const ENABLE: Pin = Pin(Port::Port1, PortPin::P6);
+
+/// Register file of the device, with the address toggling within a register pair.
+#[derive(Default)]
+struct Device {
+    registers: [u8; 8],
+    inputs: [u8; 2],
+}
+
+#[derive(Clone)]
+struct Bus(Rc<RefCell<Device>>);
+
+impl Bus {
+    fn new() -> Self {
+        let device = Device {
+            registers: [0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00, 0xFF, 0xFF],
+            ..Device::default()
+        };
+        Bus(Rc::new(RefCell::new(device)))
+    }
+
+    fn register(&self, register: Register) -> u8 {
+        self.0.borrow().registers[register.addr() as usize]
+    }
+}
+
+#[derive(Debug)]
+struct NoAcknowledge;
+
+impl embedded_hal::i2c::Error for NoAcknowledge {
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)
+    }
+}
+
+impl ErrorType for Bus {
+    type Error = NoAcknowledge;
+}
+
+impl embedded_hal_async::i2c::I2c for Bus {
+    async fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), Self::Error> {
+        if address != ADDR {
+            return Err(NoAcknowledge);
+        }
+
+        let mut device = self.0.borrow_mut();
+        let mut pointer = None;
+        for operation in operations {
+            match operation {
+                Operation::Write(bytes) => {
+                    let mut bytes = bytes.iter();
+                    if pointer.is_none() {
+                        pointer = bytes.next().map(|&addr| addr as usize);
+                    }
+                    for &value in bytes {
+                        let addr = pointer.unwrap();
+                        if addr >= 2 {
+                            device.registers[addr] = value;
+                        }
+                        pointer = Some(addr ^ 1);
+                    }
+                }
+                Operation::Read(buffer) => {
+                    for value in buffer.iter_mut() {
+                        let addr = pointer.unwrap();
+                        *value = if addr < 2 { device.inputs[addr] } else { device.registers[addr] };
+                        pointer = Some(addr ^ 1);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.transaction(address, &mut [Operation::Read(read)]).await
+    }
+
+    async fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        self.transaction(address, &mut [Operation::Write(write)]).await
+    }
+
+    async fn write_read(&mut self, address: u8, write: &[u8], read: &mut [u8]) -> Result<(), Self::Error> {
+        self.transaction(address, &mut [Operation::Write(write), Operation::Read(read)]).await
+    }
+}
+
+struct NoDelay;
+
+impl embedded_hal_async::delay::DelayUs for NoDelay {
+    async fn delay_us(&mut self, _us: u32) {}
+
+    async fn delay_ms(&mut self, _ms: u32) {}
+}
+
+fn setup() -> (Bus, Tca6416<Bus>) {
+    let bus = Bus::new();
+    let driver = Tca6416::new(bus.clone(), ADDR);
+    (bus, driver)
+}
+
+#[test]
+fn pin_direction_and_output() {
+    let (bus, mut driver) = setup();
+
+    block_on(driver.set_pin_direction(ENABLE, Direction::Output)).unwrap();
+    assert_eq!(bus.register(Register::ConfigPort1), 0xBF);
+    assert_eq!(block_on(driver.get_pin_direction(ENABLE)).unwrap(), Direction::Output);
+    assert_eq!(block_on(driver.get_pin_direction(BUTTON)).unwrap(), Direction::Input);
+
+    block_on(driver.set_pin_output_state(ENABLE, PinState::Low)).unwrap();
+    assert_eq!(bus.register(Register::OutputPort1), 0xBF);
+}
+
+#[test]
+fn read_inputs() {
+    let (bus, mut driver) = setup();
+    bus.0.borrow_mut().inputs = [0x08, 0x40];
+
+    assert_eq!(block_on(driver.read_inputs()).unwrap(), 0x4008);
+    assert_eq!(block_on(driver.read_pin(BUTTON)).unwrap(), PinState::High);
+    assert_eq!(block_on(driver.read_port(Port::Port1)).unwrap(), 0x40);
+}
+
+#[test]
+fn configure_writes_register_pairs() {
+    const CONFIG: Tca6416Config = Tca6416Config::new()
+        .with_direction(ENABLE, Direction::Output)
+        .with_output(ENABLE, PinState::Low)
+        .with_polarity_inversion(BUTTON, true);
+
+    let (bus, mut driver) = setup();
+    block_on(driver.configure(&CONFIG)).unwrap();
+
+    assert_eq!(bus.register(Register::OutputPort0), 0xFF);
+    assert_eq!(bus.register(Register::OutputPort1), 0xBF);
+    assert_eq!(bus.register(Register::PolarityInversionPort0), 0x08);
+    assert_eq!(bus.register(Register::ConfigPort0), 0xFF);
+    assert_eq!(bus.register(Register::ConfigPort1), 0xBF);
+    assert_eq!(CONFIG.direction(ENABLE), Direction::Output);
+
+    block_on(IoExpander::reset(&mut driver, &mut NoDelay)).unwrap();
+    assert_eq!(bus.register(Register::OutputPort1), 0xFF);
+    assert_eq!(bus.register(Register::PolarityInversionPort0), 0x00);
+    assert_eq!(bus.register(Register::ConfigPort1), 0xFF);
+}
+
+#[test]
+fn interrupts_cannot_be_masked() {
+    let (_, mut driver) = setup();
+
+    block_on(driver.set_pin_interrupt(BUTTON, true)).unwrap();
+    assert!(matches!(
+        block_on(driver.set_pin_interrupt(BUTTON, false)),
+        Err(TcaError::InterruptMaskUnsupported)
+    ));
+}
+
+#[test]
+fn missing_device() {
+    let mut driver = Tca6416::new(Bus::new(), 0x21);
+    assert!(matches!(block_on(driver.read_inputs()), Err(TcaError::I2c(_))));
+}
@@ -15,7 +15,9 @@ pub mod power;
 pub mod ui;
 
 pub mod i2c {
-    pub const AW9523B_I2C_ADDRESS: u8 = 0x5B;
+    use aw9523b::{Address, PinState};
+
+    pub const AW9523B_I2C_ADDRESS: Address = Address::from_straps(PinState::High, PinState::High);
 }
 
 pub type SharedI2cBus = I2c<'static, I2C2, DMA1_CH4, DMA1_CH5>;
@@ -1,5 +1,9 @@
 #![allow(dead_code)]
-use aw9523b::{Aw9523b, AwError, Pin, PinMode, PinState, Port, PortPin};
+use aw9523b::brightness::{Brightness, Curve};
+use aw9523b::config::Aw9523bConfig;
+use aw9523b::recovery::Health;
+use aw9523b::{Aw9523b, AwError, PinMode};
+use io_expander::{IoExpander, LedDimming, Pin, PinState, Port, PortPin};
 
 const BT_BUTTON: Pin = Pin(Port::Port0, PortPin::P2);
 const PLAY_BUTTON: Pin = Pin(Port::Port0, PortPin::P3);
@@ -14,44 +18,72 @@ const SOURCE_LED_R: Pin = Pin(Port::Port1, PortPin::P4);
 const SOURCE_LED_G: Pin = Pin(Port::Port1, PortPin::P6);
 const SOURCE_LED_B: Pin = Pin(Port::Port1, PortPin::P5);
 
-pub struct Ui<R, I, P, I2C> {
+/// Configuration of the AW9523B fitted on the board.
+pub const AW9523B_CONFIG: Aw9523bConfig = Aw9523bConfig::new()
+    // Buttons
+    .with_pin_mode(BT_BUTTON, PinMode::Input)
+    .with_pin_mode(PLAY_BUTTON, PinMode::Input)
+    .with_pin_mode(PLUS_BUTTON, PinMode::Input)
+    .with_pin_mode(MINUS_BUTTON, PinMode::Input)
+    .with_interrupt(BT_BUTTON, true)
+    .with_interrupt(PLAY_BUTTON, true)
+    .with_interrupt(PLUS_BUTTON, true)
+    .with_interrupt(MINUS_BUTTON, true)
+    // Status LED
+    .with_pin_mode(STATUS_LED_R, PinMode::Led)
+    .with_pin_mode(STATUS_LED_G, PinMode::Led)
+    .with_pin_mode(STATUS_LED_B, PinMode::Led)
+    // Source LED
+    .with_pin_mode(SOURCE_LED_R, PinMode::Led)
+    .with_pin_mode(SOURCE_LED_G, PinMode::Led)
+    .with_pin_mode(SOURCE_LED_B, PinMode::Led);
+
+const LED_BRIGHTNESS: Brightness = Brightness::new(Curve::Cie1931);
+
+/// User interface of the board, the buttons and LEDs being wired to an IO expander `X`.
+pub struct Ui<X: IoExpander, I, P, D> {
     is_initialized: bool,
-    io_expander: Aw9523b<I2C>,
-    io_exp_reset_gpio: R,
+    io_expander: X,
+    io_expander_config: X::Config,
+    led_brightness: Brightness,
     io_exp_int_gpio: I,
     power_button_gpio: P,
+    delay: D,
 }
 
 #[derive(Debug)]
 pub enum Error<E> {
     UsedBeforeInitialization,
-    IoExpanderError(AwError<E>),
+    IoExpanderError(E),
 }
 
-impl<E> From<AwError<E>> for Error<E> {
-    fn from(value: AwError<E>) -> Self {
+impl<E> From<E> for Error<E> {
+    fn from(value: E) -> Self {
         Error::IoExpanderError(value)
     }
 }
 
-impl<R, I, P, I2C, E> Ui<R, I, P, I2C>
+impl<X, I, P, D> Ui<X, I, P, D>
 where
-    I2C: embedded_hal_async::i2c::I2c + embedded_hal_async::i2c::I2c<Error = E>,
-    R: embedded_hal::digital::OutputPin,
+    X: IoExpander,
     P: embedded_hal::digital::InputPin,
+    D: embedded_hal_async::delay::DelayUs,
 {
     pub fn new(
-        io_expander: Aw9523b<I2C>,
-        io_exp_reset_gpio: R,
+        io_expander: X,
+        io_expander_config: X::Config,
         io_exp_int_gpio: I,
         power_button_gpio: P,
+        delay: D,
     ) -> Self {
         Self {
             is_initialized: false,
             io_expander,
-            io_exp_reset_gpio,
+            io_expander_config,
+            led_brightness: LED_BRIGHTNESS,
             io_exp_int_gpio,
             power_button_gpio,
+            delay,
         }
     }
 
@@ -59,131 +91,122 @@ where
         self.is_initialized
     }
 
-    pub fn reset(mut self) {
-        self.io_exp_reset_gpio.set_low().unwrap();
-        self.is_initialized = false;
-    }
-
-    pub async fn initialize(&mut self) -> Result<(), Error<E>> {
-        self.io_exp_reset_gpio.set_low().unwrap();
-        self.io_expander.software_reset().await?;
-
-        // Configure button GPIOs as inputs
-        self.io_expander
-            .set_pin_config(BT_BUTTON, PinMode::Input)
-            .await?;
-        self.io_expander
-            .set_pin_config(PLAY_BUTTON, PinMode::Input)
-            .await?;
-        self.io_expander
-            .set_pin_config(PLUS_BUTTON, PinMode::Input)
-            .await?;
-        self.io_expander
-            .set_pin_config(MINUS_BUTTON, PinMode::Input)
-            .await?;
-
-        // Enable interrupts on button GPIOs
-        self.io_expander
-            .enable_pin_interrupt(BT_BUTTON, true)
-            .await?;
-        self.io_expander
-            .enable_pin_interrupt(PLAY_BUTTON, true)
-            .await?;
-        self.io_expander
-            .enable_pin_interrupt(PLUS_BUTTON, true)
-            .await?;
-        self.io_expander
-            .enable_pin_interrupt(MINUS_BUTTON, true)
-            .await?;
-
-        // Configure Power LED GPIOs as LEDs
-        self.io_expander
-            .set_pin_config(STATUS_LED_R, PinMode::Led)
-            .await?;
-        self.io_expander
-            .set_pin_config(STATUS_LED_G, PinMode::Led)
-            .await?;
-        self.io_expander
-            .set_pin_config(STATUS_LED_B, PinMode::Led)
-            .await?;
-
-        // Configure BT LED GPIOs as LEDs
-        self.io_expander
-            .set_pin_config(SOURCE_LED_R, PinMode::Led)
-            .await?;
-        self.io_expander
-            .set_pin_config(SOURCE_LED_G, PinMode::Led)
-            .await?;
-        self.io_expander
-            .set_pin_config(SOURCE_LED_B, PinMode::Led)
-            .await?;
+    pub async fn initialize(&mut self) -> Result<(), Error<X::Error>> {
+        self.io_expander.reset(&mut self.delay).await?;
+        self.io_expander.probe().await?;
+        self.io_expander.configure(&self.io_expander_config).await?;
 
         self.is_initialized = true;
         Ok(())
     }
 
-    pub fn is_power_pressed(&mut self) -> Result<bool, Error<E>> {
+    pub fn is_power_pressed(&mut self) -> Result<bool, Error<X::Error>> {
         if !self.is_initialized {
             return Err(Error::UsedBeforeInitialization);
         }
         Ok(self.power_button_gpio.is_low().unwrap())
     }
 
-    pub async fn is_bt_pressed(&mut self) -> Result<bool, Error<E>> {
-        if !self.is_initialized {
-            return Err(Error::UsedBeforeInitialization);
-        }
+    pub async fn is_bt_pressed(&mut self) -> Result<bool, Error<X::Error>> {
+        self.is_button_pressed(BT_BUTTON).await
+    }
 
-        let pin = self.io_expander.read_pin(BT_BUTTON).await?;
-        Ok(matches!(pin, PinState::Low))
+    pub async fn is_play_pause_pressed(&mut self) -> Result<bool, Error<X::Error>> {
+        self.is_button_pressed(PLAY_BUTTON).await
     }
 
-    pub async fn is_play_pause_pressed(&mut self) -> Result<bool, Error<E>> {
-        if !self.is_initialized {
-            return Err(Error::UsedBeforeInitialization);
-        }
+    pub async fn is_plus_pressed(&mut self) -> Result<bool, Error<X::Error>> {
+        self.is_button_pressed(PLUS_BUTTON).await
+    }
 
-        let pin = self.io_expander.read_pin(PLAY_BUTTON).await?;
-        Ok(matches!(pin, PinState::Low))
+    pub async fn is_minus_pressed(&mut self) -> Result<bool, Error<X::Error>> {
+        self.is_button_pressed(MINUS_BUTTON).await
     }
 
-    pub async fn is_plus_pressed(&mut self) -> Result<bool, Error<E>> {
+    async fn is_button_pressed(&mut self, pin: Pin) -> Result<bool, Error<X::Error>> {
         if !self.is_initialized {
             return Err(Error::UsedBeforeInitialization);
         }
 
-        let pin = self.io_expander.read_pin(PLUS_BUTTON).await?;
+        let pin = self.io_expander.read_pin(pin).await?;
         Ok(matches!(pin, PinState::Low))
     }
+}
+
+impl<X, I, P, D> Ui<X, I, P, D>
+where
+    X: IoExpander,
+    I: embedded_hal::digital::InputPin + embedded_hal_async::digital::Wait,
+{
+    /// Checks if the IO expander reports a change of its inputs, which reading them acknowledges.
+    pub fn is_io_expander_interrupt_pending(&mut self) -> bool {
+        // Assume a change if the line cannot be read
+        self.io_exp_int_gpio.is_low().unwrap_or(true)
+    }
+
+    /// Waits for the IO expander to report a change of its inputs.
+    pub async fn wait_for_io_expander_interrupt(&mut self) {
+        // INT is active low and stays low until the inputs are read
+        let _ = self.io_exp_int_gpio.wait_for_low().await;
+    }
+}
+
+impl<X, I, P, D> Ui<X, I, P, D>
+where
+    X: LedDimming,
+{
+    /// Sets the brightness correction applied to the LED colors from the next update on.
+    pub fn set_led_brightness(&mut self, brightness: Brightness) {
+        self.led_brightness = brightness;
+    }
 
-    pub async fn is_minus_pressed(&mut self) -> Result<bool, Error<E>> {
+    pub async fn set_status_led(&mut self, r: u8, g: u8, b: u8) -> Result<(), Error<X::Error>> {
         if !self.is_initialized {
             return Err(Error::UsedBeforeInitialization);
         }
 
-        let pin = self.io_expander.read_pin(MINUS_BUTTON).await?;
-        Ok(matches!(pin, PinState::Low))
+        self.set_led(STATUS_LED_R, r).await?;
+        self.set_led(STATUS_LED_G, g).await?;
+        self.set_led(STATUS_LED_B, b).await?;
+        Ok(())
     }
 
-    pub async fn set_status_led(&mut self, r: u8, g: u8, b: u8) -> Result<(), Error<E>> {
+    pub async fn set_source_led(&mut self, r: u8, g: u8, b: u8) -> Result<(), Error<X::Error>> {
         if !self.is_initialized {
             return Err(Error::UsedBeforeInitialization);
         }
 
-        self.io_expander.set_pin_led_pwm(STATUS_LED_R, r).await?;
-        self.io_expander.set_pin_led_pwm(STATUS_LED_G, g).await?;
-        self.io_expander.set_pin_led_pwm(STATUS_LED_B, b).await?;
+        self.set_led(SOURCE_LED_R, r).await?;
+        self.set_led(SOURCE_LED_G, g).await?;
+        self.set_led(SOURCE_LED_B, b).await?;
         Ok(())
     }
 
-    pub async fn set_source_led(&mut self, r: u8, g: u8, b: u8) -> Result<(), Error<E>> {
+    async fn set_led(&mut self, pin: Pin, level: u8) -> Result<(), Error<X::Error>> {
+        let level = self.led_brightness.apply(pin, level);
+        self.io_expander.set_led_level(pin, level).await?;
+        Ok(())
+    }
+}
+
+impl<I2C, R, I, P, D, E> Ui<Aw9523b<I2C, R>, I, P, D>
+where
+    I2C: embedded_hal_async::i2c::I2c + embedded_hal_async::i2c::I2c<Error = E>,
+    R: embedded_hal::digital::OutputPin,
+{
+    /// Holds the IO expander in reset until the next initialization.
+    pub fn reset(&mut self) -> Result<(), Error<AwError<E>>> {
+        self.is_initialized = false;
+        self.io_expander.hold_in_reset()?;
+        Ok(())
+    }
+
+    /// Checks that the IO expander is still configured, restoring its configuration if it was reset.
+    pub async fn check_io_expander(&mut self) -> Result<Health, Error<AwError<E>>> {
         if !self.is_initialized {
             return Err(Error::UsedBeforeInitialization);
         }
 
-        self.io_expander.set_pin_led_pwm(SOURCE_LED_R, r).await?;
-        self.io_expander.set_pin_led_pwm(SOURCE_LED_G, g).await?;
-        self.io_expander.set_pin_led_pwm(SOURCE_LED_B, b).await?;
-        Ok(())
+        Ok(self.io_expander.check_and_recover().await?)
     }
 }
@@ -3,15 +3,78 @@ use crate::bsp::{I2cDeviceOnSharedBus, IoExpanderIntGpio, IoExpanderResetGpio, P
 use super::dispatcher;
 use crate::bsp;
 use actor::*;
-use aw9523b::Aw9523b;
-use buttons::{Buttons, Event, Id, Kind, Length, Ms, RepeatedPressMode};
-use defmt::{info, Format};
+use aw9523b::recovery::Health;
+use aw9523b::{Aw9523b, RetryPolicy};
+use buttons::{AutoRepeat, ButtonOverrides, Buttons, Chord, EmbassyClock, Event, Gesture, Id, IdSet, Length, Ms};
+use defmt::{error, info, warn, Format};
+use embassy_futures::select::select;
+use embassy_time::{Delay, Duration, Instant, Timer};
 
 pub const QUEUE_SIZE: usize = 3;
 pub const IDLE_TIMEOUT_MS: u64 = 1000;
 
-type UiBsp =
-    bsp::ui::Ui<IoExpanderResetGpio, IoExpanderIntGpio, PowerButtonGpio, I2cDeviceOnSharedBus>;
+/// Interval at which the power button, not wired to an interrupt, is read.
+const POWER_BUTTON_POLL_MS: u64 = 10;
+
+const POWER_BUTTON: Id = Id(0);
+const BT_BUTTON: Id = Id(1);
+const PLAY_BUTTON: Id = Id(2);
+const PLUS_BUTTON: Id = Id(3);
+const MINUS_BUTTON: Id = Id(4);
+
+/// Plus and Minus, held for a factory reset.
+const FACTORY_RESET_CHORD: Id = Id(5);
+/// BT and Play, to clear the pairings.
+const CLEAR_PAIRINGS_CHORD: Id = Id(6);
+
+/// Power button, the four buttons on the IO expander and the chords.
+const BUTTON_COUNT: usize = 7;
+
+static CHORDS: [Chord; 2] = [
+    Chord::new(FACTORY_RESET_CHORD, IdSet::empty().with(PLUS_BUTTON).with(MINUS_BUTTON)),
+    Chord::new(CLEAR_PAIRINGS_CHORD, IdSet::empty().with(BT_BUTTON).with(PLAY_BUTTON)),
+];
+
+/// Short, short, long on Play.
+const FIRMWARE_VERSION_GESTURE: u8 = 0;
+
+static GESTURES: [Gesture<'static>; 1] = [Gesture::new(
+    FIRMWARE_VERSION_GESTURE,
+    PLAY_BUTTON,
+    &[Length::Short, Length::Short, Length::Long],
+    Ms(400),
+)];
+
+/// Volume steps while Plus or Minus is held, speeding up to 20 steps per second.
+const VOLUME_REPEAT: AutoRepeat = AutoRepeat {
+    initial_delay: Ms(400),
+    interval: Ms(200),
+    min_interval: Ms(50),
+    acceleration: Ms(25),
+};
+
+static OVERRIDES: [ButtonOverrides; 3] = [
+    // Not turned off by a brush
+    ButtonOverrides {
+        long_press_duration: Some(Ms(2000)),
+        ..ButtonOverrides::new(POWER_BUTTON)
+    },
+    ButtonOverrides {
+        auto_repeat: Some(Some(VOLUME_REPEAT)),
+        ..ButtonOverrides::new(PLUS_BUTTON)
+    },
+    ButtonOverrides {
+        auto_repeat: Some(Some(VOLUME_REPEAT)),
+        ..ButtonOverrides::new(MINUS_BUTTON)
+    },
+];
+
+type UiBsp = bsp::ui::Ui<
+    Aw9523b<I2cDeviceOnSharedBus, IoExpanderResetGpio>,
+    IoExpanderIntGpio,
+    PowerButtonGpio,
+    Delay,
+>;
 
 #[derive(Format)]
 pub enum Message {
@@ -22,7 +85,9 @@ pub enum Message {
 pub struct Ui {
     dispatcher_inbox: DynamicInbox<dispatcher::Message>,
     ui: UiBsp,
-    buttons: Buttons<'static, Self>,
+    buttons: Buttons<'static, EmbassyClock, BUTTON_COUNT>,
+    /// Buttons on the IO expander pressed when it was last read, `None` if that failed.
+    io_expander_buttons: Option<IdSet>,
 }
 
 impl Ui {
@@ -33,33 +98,35 @@ impl Ui {
         io_exp_reset_gpio: IoExpanderResetGpio,
         io_exp_int_gpio: IoExpanderIntGpio,
     ) -> Self {
-        let io_expander = Aw9523b::new(i2c_device, bsp::i2c::AW9523B_I2C_ADDRESS);
+        // The bus is shared, other controllers and a device waking up can make a transfer fail once
+        let io_expander = Aw9523b::new(i2c_device, bsp::i2c::AW9523B_I2C_ADDRESS)
+            .with_retry_policy(RetryPolicy::new(3))
+            .with_reset_pin(io_exp_reset_gpio);
 
         let ui = bsp::ui::Ui::new(
             io_expander,
-            io_exp_reset_gpio,
+            bsp::ui::AW9523B_CONFIG,
             io_exp_int_gpio,
             power_button_gpio,
+            Delay,
         );
 
         let buttons_config = buttons::Config {
-            short_press_duration: Ms(50),
-            medium_press_duration: Ms(1000),
-            long_press_duration: Ms(5000),
-            very_long_press_duration: Ms(30000),
-            hold_event_interval: Ms(100),
-            repeated_press_threshold_duration: Ms(500),
-            buttons_with_repeated_press_support: None,
-            repeated_press_mode: RepeatedPressMode::Immediate,
             enable_raw_press_release_events: true,
+            chords: &CHORDS,
+            chord_window: Ms(80),
+            gestures: &GESTURES,
+            overrides: &OVERRIDES,
+            ..buttons::Config::DEFAULT
         };
 
-        let buttons: Buttons<'_, Self> = Buttons::new(buttons_config);
+        let buttons: Buttons<'_, EmbassyClock, BUTTON_COUNT> = Buttons::new(buttons_config, EmbassyClock);
 
         Self {
             dispatcher_inbox,
             ui,
             buttons,
+            io_expander_buttons: None,
         }
     }
 
@@ -70,15 +137,51 @@ impl Ui {
     fn on_power_on(&mut self) {
         info!("Power on");
     }
-}
 
-impl buttons::Handler for Ui {
-    async fn on_event(&mut self, button: buttons::Id, event: Event) {
-        info!("Got {} for button {}", event, button);
+    /// Reads the buttons pressed, the ones on the IO expander only when it reports a change.
+    async fn read_buttons(&mut self) -> Option<IdSet> {
+        if self.io_expander_buttons.is_none() || self.ui.is_io_expander_interrupt_pending() {
+            self.io_expander_buttons = self.read_io_expander_buttons().await;
+        }
+
+        match self.ui.is_power_pressed() {
+            Ok(true) => Some(self.io_expander_buttons?.with(POWER_BUTTON)),
+            Ok(false) => self.io_expander_buttons,
+            Err(_) => {
+                error!("Failed to read the power button");
+                None
+            }
+        }
     }
 
-    fn get_current_timestamp(&self) -> buttons::Ms {
-        buttons::Ms(0)
+    async fn read_io_expander_buttons(&mut self) -> Option<IdSet> {
+        let buttons = [
+            (BT_BUTTON, self.ui.is_bt_pressed().await),
+            (PLAY_BUTTON, self.ui.is_play_pause_pressed().await),
+            (PLUS_BUTTON, self.ui.is_plus_pressed().await),
+            (MINUS_BUTTON, self.ui.is_minus_pressed().await),
+        ];
+
+        let mut pressed = IdSet::empty();
+        for (button, is_pressed) in buttons {
+            match is_pressed {
+                Ok(true) => pressed.insert(button),
+                Ok(false) => {}
+                Err(_) => {
+                    error!("Failed to read button {}", button);
+                    return None;
+                }
+            }
+        }
+        Some(pressed)
+    }
+
+    fn on_button_event(&mut self, button: Id, event: Event) {
+        info!("Got {} for button {}", event, button);
+
+        if event == Event::Gesture(FIRMWARE_VERSION_GESTURE) {
+            info!("Firmware version {}", env!("CARGO_PKG_VERSION"));
+        }
     }
 }
 
@@ -90,9 +193,36 @@ impl ActorRuntime for Ui {
     }
 
     async fn on_idle(&mut self) {
-        // let is_power_pressed = self.ui.is_power_pressed().unwrap();
-        // let input = if is_power_pressed { Some(Id(0)) } else { None };
-        // self.buttons.process_input(self, input);
+        match self.ui.check_io_expander().await {
+            Ok(Health::Ok) => {}
+            Ok(Health::Restored) => warn!("IO expander was reset, configuration restored"),
+            Err(_) => error!("Failed to check the IO expander"),
+        }
+    }
+
+    async fn wait_for_wake_up(&mut self) {
+        // Events are due at the deadline even if no button changes until then
+        let poll_at = Instant::now() + Duration::from_millis(POWER_BUTTON_POLL_MS);
+        let wake_up_at = match self.buttons.next_deadline() {
+            Some(Ms(deadline)) => poll_at.min(Instant::from_millis(deadline)),
+            None => poll_at,
+        };
+
+        if self.io_expander_buttons.is_none() {
+            // The interrupt stays pending until the IO expander can be read again
+            Timer::at(wake_up_at).await;
+        } else {
+            select(self.ui.wait_for_io_expander_interrupt(), Timer::at(wake_up_at)).await;
+        }
+    }
+
+    async fn on_wake_up(&mut self) {
+        let Some(pressed) = self.read_buttons().await else {
+            return;
+        };
+        for (button, event) in self.buttons.process_input(pressed).iter() {
+            self.on_button_event(button, event);
+        }
     }
 
     async fn on_message_received(&mut self, message: Self::Message) {